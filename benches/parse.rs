@@ -0,0 +1,34 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use wglib::{Article, ParseBackend, ParseOptions, URL};
+
+/// A page with thousands of links that are all on a single line, like the HTML that
+/// Wikipedia serves for some of its longest articles.
+fn large_page() -> String {
+    let mut site = String::from("<div id=\"mw-content-text\">");
+    for i in 0..5000 {
+        site.push_str(&format!(
+            "<p>Überblick über Nummer {} – <a href=\"/wiki/Article_{}\" title=\"Article {}\">Article {}</a>.</p>",
+            i, i, i, i
+        ));
+    }
+    site.push_str("</div>\n");
+    site
+}
+
+fn parse(c: &mut Criterion) {
+    let site = large_page();
+    let url = URL::new("/wiki/Large").unwrap();
+    let scan = ParseOptions {
+        backend: ParseBackend::Scan,
+        ..ParseOptions::default()
+    };
+    c.bench_function("scan large page", |b| {
+        b.iter(|| Article::parse_with(url.clone(), black_box(site.clone()), &scan).unwrap())
+    });
+    c.bench_function("select large page", |b| {
+        b.iter(|| Article::parse(url.clone(), black_box(site.clone())).unwrap())
+    });
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
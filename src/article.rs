@@ -1,8 +1,16 @@
 use super::*;
-use reqwest;
-use std::collections::{HashMap, HashSet, VecDeque};
+use futures::channel::oneshot;
+use futures::future::{self, Either, FutureExt, Shared};
+use futures::stream::{self, LocalBoxStream, Stream, StreamExt};
+use scraper::{ElementRef, Html, Selector};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::error::Error;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use thiserror::Error;
+use throttle::{RateLimiter, Throttle};
 
 /// A struct representing a Wikipedia article with attributes like
 /// the URL, related articles and eventually more.
@@ -10,8 +18,157 @@ use thiserror::Error;
 pub struct Article {
     /// URL of the article; where you'd find it in your web browser.
     pub url: URL,
-    /// All the URLs of other articles that are referenced within the article.
+    /// All the URLs of other articles that are referenced within the article. They are
+    /// in no particular order, see Article::references_sorted.
     pub references: HashSet<URL>,
+    /// Links to Wikipedia pages that were found in the article but rejected, e.g. because
+    /// they are in a blacklisted namespace. This is only recorded if requested through
+    /// `ParseOptions::record_dropped`, otherwise it's `None`.
+    pub dropped: Option<Vec<DroppedLink>>,
+    /// The plain text of the article without any markup. This is only fetched if
+    /// requested through CollectorBuilder::with_content, otherwise it's `None`.
+    pub content: Option<String>,
+    /// The names of the categories the article is in, e.g. `Trees` for a link to
+    /// `/wiki/Category:Trees`. These are never part of the references.
+    pub categories: HashSet<String>,
+    /// The HTML the article was parsed from. This is only kept if requested through
+    /// CollectorBuilder::retain_html, otherwise it's `None`.
+    pub html: Option<String>,
+}
+
+/// A link that was found while parsing an article but is not a reference to
+/// a valid article, together with the reason it was rejected.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DroppedLink {
+    /// The link as it was found in the HTML, e.g. `/wiki/Help:Contents`.
+    pub link: String,
+    /// A human readable reason why `URL::new` rejected the link.
+    pub reason: String,
+}
+
+/// How many of the links on a page became references, see Article::parse_with_coverage.
+/// A lot of links that were not accepted may mean that the parser misses some.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseCoverage {
+    /// The number of `<a>` elements on the whole page.
+    pub anchors: usize,
+    /// The number of those that link to a Wikipedia page, i.e. to `/wiki/...`.
+    pub wiki_anchors: usize,
+    /// The number of links that were accepted as references. Several links to the same
+    /// article count separately, so this can be compared to the numbers above.
+    pub accepted: usize,
+}
+
+/// ParseOptions contains everything that may be configured about how the HTML
+/// of an article is turned into an Article in Article::parse_with.
+///
+/// The default options give the same result as Article::parse.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Whether links that are rejected as references should be recorded in
+    /// Article::dropped for later inspection. They are never part of the references.
+    pub record_dropped: bool,
+    /// How the titles of referenced articles are normalized.
+    pub case_sensitivity: CaseSensitivity,
+    /// How the links are found in the HTML.
+    pub backend: ParseBackend,
+    /// Whether the links in the reference section and everything after it are left out,
+    /// as well as the ones in lists of references anywhere in the article. The section
+    /// is recognised by its heading, see `WIKI_REFERENCE_HEADINGS`. This only works with
+    /// ParseBackend::Html.
+    pub ignore_reference_section: bool,
+}
+
+/// ParseBackend determines how the links of an article are found in its HTML.
+///
+/// The default is ParseBackend::Html with `WIKI_CONTENT_SELECTOR`, i.e. the links in the
+/// content of the article, but not the ones in the navigation around it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseBackend {
+    /// Every line is scanned for links. This is fast, but finds every link on the page,
+    /// including the ones in navigation boxes and the like. It also misses links whose
+    /// `href` is not their first attribute or that are split across lines.
+    Scan,
+    /// The HTML is parsed properly and only the links in the elements that match the
+    /// given CSS selector are used, e.g. `.mw-parser-output > p a` for links in paragraphs.
+    /// Use ParseBackend::html to make sure the selector is valid.
+    Html(String),
+}
+
+impl Default for ParseBackend {
+    fn default() -> Self {
+        ParseBackend::Html(String::from(WIKI_CONTENT_SELECTOR))
+    }
+}
+
+impl ParseBackend {
+    /// Creates a ParseBackend::Html with the given selector if it is a valid CSS selector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wglib::ParseBackend;
+    ///
+    /// assert!(ParseBackend::html("#mw-content-text p a").is_ok());
+    /// assert!(ParseBackend::html("p >> a").is_err());
+    /// ```
+    pub fn html(selector: &str) -> Result<Self, ArticleErr> {
+        match Selector::parse(selector) {
+            Ok(_) => Ok(ParseBackend::Html(String::from(selector))),
+            Err(_) => Err(ArticleErr::InvalidSelector(String::from(selector))),
+        }
+    }
+}
+
+/// Determines what happens to articles without any references, e.g. stubs, pages that
+/// could not be parsed properly or error pages that were served with a success status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmptyReferences {
+    /// Such articles are collected like any other article and become dead ends.
+    #[default]
+    Keep,
+    /// Such articles are cached, but left out of the results of Collector::get_list and
+    /// everything that builds on it, e.g. neighbourhoods.
+    Drop,
+    /// Getting such an article fails with CollectionErr::NoReferences. The article is not
+    /// cached, so it is requested again the next time.
+    Fail,
+}
+
+/// Determines what happens when a seed of a crawl, e.g. the origin of a path, turns out
+/// to be a disambiguation page. Such pages link to lots of unrelated topics, so the crawl
+/// is unlikely to be what was intended. See Article::is_disambiguation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisambiguationSeeds {
+    /// The crawl goes on, but ProgressEvent::DisambiguationSeed is emitted.
+    #[default]
+    Warn,
+    /// The crawl fails with CollectionErr::DisambiguationSeed.
+    Fail,
+}
+
+/// Something noteworthy that happened during a crawl. See CollectorBuilder::on_progress.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// A seed of the crawl is a disambiguation page, so a more specific article should
+    /// probably be used instead.
+    DisambiguationSeed(URL),
+    /// An article was requested and parsed, i.e. it was not in the cache.
+    Fetched(URL),
+    /// A list of articles is complete, see Collector::get_list. Of the `requested` ones,
+    /// `cache_hits` were in the cache already.
+    ListCompleted { requested: usize, cache_hits: usize },
+    /// A neighbourhood crawl goes one level deeper. The `frontier` contains the articles
+    /// that are requested next and `seen` is the number of articles encountered before.
+    FrontierExpanded { frontier: usize, seen: usize },
+    /// Collector::all_pairs_paths has found a path with the given number of links.
+    PathFound { from: URL, to: URL, length: usize },
+    /// Some lines of a cache file were skipped because they are not valid articles, see
+    /// Collector::load_cache.
+    InvalidCacheEntries { path: String, skipped: usize },
+    /// The cache could not be written when the Collector was dropped, see
+    /// CollectorBuilder::persist. This contains the path and the error.
+    CacheNotSaved { path: String, error: String },
 }
 
 /// ArticleErr is an enum that contains possible error values that
@@ -22,354 +179,4102 @@ pub struct Article {
 pub enum ArticleErr {
     #[error("Line ended while parsing URL")]
     UnexpectedEOL,
+    #[error("Unexpected response from the API.")]
+    UnexpectedResponse,
+    #[error("Invalid CSS selector: {0}")]
+    InvalidSelector(String),
 }
 
+/// The parameters a request to the MediaWiki API needs to get the next part of a list that
+/// did not fit into one response, as given in its `continue` object, ordered by name.
+pub type Continuation = Vec<(String, String)>;
+
 impl Article {
     pub fn new(url: URL) -> Self {
         Article {
-            url: url,
+            url,
             references: HashSet::new(),
+            dropped: None,
+            content: None,
+            categories: HashSet::new(),
+            html: None,
         }
     }
+
+    /// Returns the references of the article in ascending order, so they can be
+    /// processed the same way in every run.
+    pub fn references_sorted(&self) -> Vec<URL> {
+        let mut refs: Vec<_> = self.references.iter().cloned().collect();
+        refs.sort();
+        refs
+    }
+
+    /// Parses the HTML of an article using the default ParseOptions.
     pub fn parse(url: URL, site: String) -> Result<Self, Box<dyn Error>> {
+        Article::parse_with(url, site, &ParseOptions::default())
+    }
+
+    /// Parses the HTML of an article and collects all the references to other articles.
+    pub fn parse_with(url: URL, site: String, opts: &ParseOptions) -> Result<Self, Box<dyn Error>> {
+        let links = Article::links(&site, opts, url.domain_config())?;
+        Ok(Article::from_links(url, links, opts).0)
+    }
+
+    /// Returns the canonical URL of an article from its HTML if it differs from the URL
+    /// that was requested, i.e. if the requested URL is a redirect. See `CANONICAL_PREFIX`.
+    fn redirect_target(url: &URL, site: &str, case: CaseSensitivity) -> Option<URL> {
+        let start = site.find(CANONICAL_PREFIX)? + CANONICAL_PREFIX.len();
+        let href = &site[start..start + site[start..].find('"')?];
+        let target = URL::new_in(href, url.domain_config(), case).ok()?;
+        if target == *url {
+            None
+        } else {
+            Some(target)
+        }
+    }
+
+    /// Does the same as Article::parse_with, but also counts how many of the links on the
+    /// page became references. This parses the whole page once more, so it's a lot slower.
+    pub fn parse_with_coverage(
+        url: URL,
+        site: String,
+        opts: &ParseOptions,
+    ) -> Result<(Self, ParseCoverage), Box<dyn Error>> {
+        let links = Article::links(&site, opts, url.domain_config())?;
+        let (a, accepted) = Article::from_links(url, links, opts);
+        let html = Html::parse_document(&site);
+        let mut coverage = ParseCoverage {
+            accepted,
+            ..ParseCoverage::default()
+        };
+        for e in html.select(&Selector::parse("a").unwrap()) {
+            coverage.anchors += 1;
+            if e.value()
+                .attr("href")
+                .is_some_and(|h| h.starts_with(WIKI_ARTICLE_PREFIX))
+            {
+                coverage.wiki_anchors += 1;
+            }
+        }
+        Ok((a, coverage))
+    }
+
+    /// Finds the targets of all the links to Wikipedia pages with the configured backend.
+    fn links(
+        site: &str,
+        opts: &ParseOptions,
+        domain: &DomainConfig,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        match &opts.backend {
+            ParseBackend::Scan => Article::scan_links(site),
+            ParseBackend::Html(selector) => {
+                Article::select_links(site, selector, domain, opts.ignore_reference_section)
+            }
+        }
+    }
+
+    /// Turns the links that were found in an article into its references. Returns the
+    /// article together with the number of links that were accepted as references.
+    fn from_links(url: URL, links: Vec<String>, opts: &ParseOptions) -> (Self, usize) {
+        let mut accepted = 0;
         let mut refs = HashSet::new();
-        let lines = site.lines();
-        for mut line in lines {
-            while !line.is_empty() {
-                if line.starts_with("<a href=\"/wiki/") {
-                    line = line.strip_prefix(REFERENCE_PREFIX).unwrap_or("");
-                    let end;
-                    match line.find('"') {
-                        Some(i) => end = i,
-                        None => {
-                            return Err(Box::new(ArticleErr::UnexpectedEOL));
-                        }
-                    }
-                    if let Ok(ref_url) = URL::new(&line[..end]) {
-                        refs.insert(ref_url);
+        let mut dropped = Vec::new();
+        let mut categories = HashSet::new();
+        let domain = url.domain_config();
+        for link in links {
+            match URL::new_in(&link, domain, opts.case_sensitivity) {
+                Ok(ref_url) => {
+                    accepted += 1;
+                    refs.insert(ref_url);
+                }
+                Err(_) if link.starts_with(domain.category_prefix) => {
+                    let name = &link[domain.category_prefix.len()..];
+                    let name = name.split('#').next().unwrap_or("");
+                    if !name.is_empty() {
+                        categories.insert(name.replace('_', " "));
                     }
-                    line = &line[end..];
-                    continue;
                 }
-                // Strip one character from the left.
-                line = line
-                    .chars()
-                    .next()
-                    .map(|c| &line[c.len_utf8()..])
-                    .unwrap_or("");
-            }
-        }
-        let mut v: Vec<String> = refs.iter().map(|x| x.to_string()).collect();
-        v.sort();
-        Ok(Article {
-            url: url,
+                Err(e) if opts.record_dropped => dropped.push(DroppedLink {
+                    reason: e.to_string(),
+                    link,
+                }),
+                Err(_) => {}
+            }
+        }
+        dropped.sort();
+        dropped.dedup();
+        let a = Article {
+            url,
             references: refs,
+            dropped: if opts.record_dropped {
+                Some(dropped)
+            } else {
+                None
+            },
+            content: None,
+            categories,
+            html: None,
+        };
+        (a, accepted)
+    }
+
+    /// Finds the targets of all the links to Wikipedia pages by scanning the HTML line by
+    /// line, see ParseBackend::Scan.
+    ///
+    /// Every line is only scanned once from left to right, so even pages that are a
+    /// single huge line are parsed in linear time.
+    fn scan_links(site: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        let start = format!("{}{}", REFERENCE_PREFIX, WIKI_ARTICLE_PREFIX);
+        let mut links = Vec::new();
+        for mut line in site.lines() {
+            while let Some(i) = line.find(&start) {
+                line = &line[i + REFERENCE_PREFIX.len()..];
+                let end = line.find('"').ok_or(ArticleErr::UnexpectedEOL)?;
+                links.push(String::from(&line[..end]));
+                line = &line[end..];
+            }
+        }
+        Ok(links)
+    }
+
+    /// Finds the targets of all the links to Wikipedia pages in the parts of the HTML
+    /// that match the given selector, see ParseBackend::Html.
+    ///
+    /// The links to categories are listed below the content of an article, so they are
+    /// taken from the whole page instead.
+    fn select_links(
+        site: &str,
+        selector: &str,
+        domain: &DomainConfig,
+        ignore_reference_section: bool,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let selector = Selector::parse(selector)
+            .map_err(|_| ArticleErr::InvalidSelector(String::from(selector)))?;
+        let anchors = Selector::parse("a[href]").unwrap();
+        let html = Html::parse_document(site);
+        let ignored = if ignore_reference_section {
+            Article::reference_links(&html, domain.reference_headings)
+        } else {
+            HashSet::new()
+        };
+        let mut links = Vec::new();
+        for e in html.select(&selector) {
+            // The selected element may be a link itself or contain links.
+            for a in e.select(&anchors).chain(Some(e)) {
+                if ignored.contains(&a.id()) {
+                    continue;
+                }
+                match a.value().attr("href") {
+                    Some(href)
+                        if a.value().name() == "a" && href.starts_with(WIKI_ARTICLE_PREFIX) =>
+                    {
+                        links.push(String::from(href))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        for a in html.select(&anchors) {
+            match a.value().attr("href") {
+                Some(href) if href.starts_with(domain.category_prefix) => {
+                    links.push(String::from(href))
+                }
+                _ => {}
+            }
+        }
+        Ok(links)
+    }
+
+    /// Returns the ids of the links that come after the first `h2` with one of the given
+    /// headings and of the ones in lists of references, i.e. `<ol class="references">`.
+    ///
+    /// A heading is recognised by its text as well as by its id (or the id of an element
+    /// in it), which is the heading with underscores instead of spaces.
+    fn reference_links(html: &Html, headings: &[&str]) -> HashSet<ego_tree::NodeId> {
+        let mut ignored = HashSet::new();
+        let mut in_section = false;
+        // The nodes are visited in the order they appear in the document.
+        for n in html.root_element().descendants() {
+            let e = match ElementRef::wrap(n) {
+                Some(e) => e,
+                None => continue,
+            };
+            match e.value().name() {
+                "h2" if !in_section => {
+                    let text = e.text().collect::<String>();
+                    in_section = headings.iter().any(|h| {
+                        let id = h.replace(' ', "_");
+                        text.trim() == *h
+                            || e.value().id() == Some(&id)
+                            || e.descendants()
+                                .filter_map(ElementRef::wrap)
+                                .any(|d| d.value().id() == Some(&id))
+                    });
+                }
+                "a" if in_section
+                    || e.ancestors().filter_map(ElementRef::wrap).any(|p| {
+                        p.value().name() == "ol" && p.value().classes().any(|c| c == "references")
+                    }) =>
+                {
+                    ignored.insert(e.id());
+                }
+                _ => {}
+            }
+        }
+        ignored
+    }
+
+    /// Returns the URL that has to be requested to get the input of Article::parse_extract.
+    pub fn extract_url(url: &URL) -> String {
+        url.api_url(
+            "titles",
+            &[
+                ("action", "query"),
+                ("prop", "extracts"),
+                ("explaintext", "1"),
+                ("redirects", "1"),
+            ],
+        )
+    }
+
+    /// Takes the response of the MediaWiki API to a request for the plain text extract of
+    /// an article (see Article::extract_url) and returns the text. If the article does not
+    /// exist, there is no text.
+    pub fn parse_extract(response: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let v: serde_json::Value = serde_json::from_str(response)?;
+        let page = v["query"]["pages"]
+            .get(0)
+            .ok_or(ArticleErr::UnexpectedResponse)?;
+        Ok(page["extract"].as_str().map(String::from))
+    }
+
+    /// Returns the URL that has to be requested to get the input of Article::from_api, i.e.
+    /// the links and categories of the given article. `cont` contains the continuation
+    /// parameters of the previous response, if there was one.
+    pub fn links_url(url: &URL, cont: &Continuation) -> String {
+        let mut params = vec![
+            ("action", "query"),
+            ("prop", "links|categories"),
+            ("plnamespace", "0"),
+            ("pllimit", "max"),
+            ("cllimit", "max"),
+            ("redirects", "1"),
+        ];
+        params.extend(cont.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+        url.api_url("titles", &params)
+    }
+
+    /// Builds an article from the response of the MediaWiki API to a request for its
+    /// links (see Article::links_url), using the default ParseOptions. Unlike the HTML,
+    /// the response only lists links to other articles, none of the ones in the navigation.
+    pub fn from_api(url: URL, json: &str) -> Result<Self, Box<dyn Error>> {
+        Article::from_api_with(url, &[json], &ParseOptions::default())
+    }
+
+    /// Does the same as Article::from_api, but for all the responses it took to get the
+    /// links of an article, see Article::api_continuation. The backend and the reference
+    /// section in the options don't matter here.
+    ///
+    /// If the requested article is a redirect, the article it points to is built.
+    pub fn from_api_with(
+        mut url: URL,
+        responses: &[&str],
+        opts: &ParseOptions,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut links = Vec::new();
+        for r in responses {
+            let v: serde_json::Value = serde_json::from_str(r)?;
+            let page = v["query"]["pages"]
+                .get(0)
+                .ok_or(ArticleErr::UnexpectedResponse)?;
+            if let Some(t) = page["title"].as_str() {
+                let title = format!("{}{}", WIKI_ARTICLE_PREFIX, t);
+                if let Ok(target) = URL::new_in(&title, url.domain_config(), opts.case_sensitivity)
+                {
+                    url = target;
+                }
+            }
+            for key in ["links", "categories"].iter() {
+                let titles = page[*key].as_array().into_iter().flatten();
+                links.extend(
+                    titles
+                        .filter_map(|l| l["title"].as_str())
+                        .map(|t| format!("{}{}", WIKI_ARTICLE_PREFIX, t.replace(' ', "_"))),
+                );
+            }
+        }
+        Ok(Article::from_links(url, links, opts).0)
+    }
+
+    /// Returns the continuation parameters of the next request if a response of the
+    /// MediaWiki API didn't contain the whole list, see Continuation.
+    pub fn api_continuation(json: &str) -> Result<Option<Continuation>, Box<dyn Error>> {
+        let v: serde_json::Value = serde_json::from_str(json)?;
+        Ok(Article::continuation(&v))
+    }
+
+    fn continuation(v: &serde_json::Value) -> Option<Continuation> {
+        v["continue"].as_object().map(|c| {
+            c.iter()
+                .filter_map(|(k, v)| v.as_str().map(|v| (k.clone(), String::from(v))))
+                .collect()
         })
     }
 
+    /// Returns the URL that has to be requested to get the input of Article::parse_backlinks,
+    /// i.e. the articles that link to the given one. `cont` contains the continuation
+    /// parameters of the previous response, if there was one.
+    pub fn backlinks_url(url: &URL, cont: &Continuation) -> String {
+        let mut params = vec![
+            ("action", "query"),
+            ("list", "backlinks"),
+            ("blnamespace", "0"),
+            ("bllimit", "max"),
+        ];
+        params.extend(cont.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+        url.api_url("bltitle", &params)
+    }
+
+    /// Takes the response of the MediaWiki API to a request for the backlinks of an article
+    /// (see Article::backlinks_url) and returns the articles that link to it, together with
+    /// the continuation parameters of the next request if there are more of them.
+    pub fn parse_backlinks(
+        response: &str,
+        domain: &'static DomainConfig,
+        case: CaseSensitivity,
+    ) -> Result<(Vec<URL>, Option<Continuation>), Box<dyn Error>> {
+        let v: serde_json::Value = serde_json::from_str(response)?;
+        let links = v["query"]["backlinks"]
+            .as_array()
+            .ok_or(ArticleErr::UnexpectedResponse)?;
+        let urls = links
+            .iter()
+            .filter_map(|l| l["title"].as_str())
+            .filter_map(|t| {
+                URL::new_in(&format!("{}{}", WIKI_ARTICLE_PREFIX, t), domain, case).ok()
+            })
+            .collect();
+        Ok((urls, Article::continuation(&v)))
+    }
+
     pub fn get_url(&self) -> URL {
         self.url.clone()
     }
+
+    /// Whether the article is a disambiguation page, i.e. in one of the disambiguation
+    /// categories of its domain. See `DomainConfig::disambiguation_categories`.
+    pub fn is_disambiguation(&self) -> bool {
+        self.url
+            .domain_config()
+            .disambiguation_categories
+            .iter()
+            .any(|c| self.categories.contains(*c))
+    }
+
+    /// A rough estimate of the memory used by this article in bytes, i.e. the length
+    /// of its own URL, of all the URLs it references and of its HTML if it is kept.
+    pub fn estimated_size(&self) -> usize {
+        self.url.as_str().len()
+            + self
+                .references
+                .iter()
+                .map(|r| r.as_str().len())
+                .sum::<usize>()
+            + self.html.as_ref().map_or(0, |h| h.len())
+    }
 }
 
 /// A structs to handle requests to look up one or more specific articles,
 /// a neighbourhood around and article or even paths between two articles.
-/// 
+///
 /// No make this more efficient a Collector has a cache and a reqwest client
 /// to limit overhead and the number of actual GET requests sent and articles
-/// parsed.
+/// parsed. The client is hidden behind a Fetcher, so it may be replaced.
 pub struct Collector {
     cache: HashMap<URL, Article>,
-    processed: usize,
-    client: reqwest::Client,
+    redirects: HashMap<URL, URL>,
+    cache_size: usize,
+    dirty: HashSet<URL>,
+    /// Articles that were evicted before they were saved, see Collector::save_cache.
+    unsaved: HashMap<URL, Article>,
+    memory_limit: Option<usize>,
+    cache_capacity: Option<usize>,
+    last_used: HashMap<URL, u64>,
+    lru: BTreeMap<u64, URL>,
+    tick: u64,
+    lookups: usize,
+    cache_hits: usize,
+    processed: AtomicUsize,
+    fetcher: Box<dyn Fetcher>,
+    concurrency: usize,
+    throttle: Option<Throttle>,
+    rate_limiter: Option<RateLimiter>,
+    parse_options: ParseOptions,
+    links_api: bool,
+    with_content: bool,
+    retain_html: bool,
+    min_out_degree: usize,
+    retry_policy: RetryPolicy,
+    retry_budget: Option<AtomicUsize>,
+    frontier_limit: Option<usize>,
+    max_depth: Option<usize>,
+    empty_references: EmptyReferences,
+    disambiguation_seeds: DisambiguationSeeds,
+    on_progress: Option<Box<dyn Fn(ProgressEvent) + Send + Sync>>,
+    persist_path: Option<String>,
+    timings: Option<Timings>,
+    time_limit: Option<(Duration, String)>,
+    rate_limit_markers: Vec<String>,
+}
+
+/// A builder for Collectors that are not configured with the default options.
+///
+/// # Examples
+///
+/// ```
+/// use wglib::Collector;
+///
+/// let c = Collector::builder().record_dropped_links(true).build();
+/// ```
+#[derive(Default)]
+pub struct CollectorBuilder {
+    fetcher: Option<Box<dyn Fetcher>>,
+    http: HttpOptions,
+    concurrency: Option<usize>,
+    backpressure: Option<Backpressure>,
+    politeness: Option<Politeness>,
+    rate_limit: Option<Duration>,
+    parse_options: ParseOptions,
+    memory_limit: Option<usize>,
+    cache_capacity: Option<usize>,
+    links_api: bool,
+    with_content: bool,
+    retain_html: bool,
+    min_out_degree: usize,
+    retry_policy: Option<RetryPolicy>,
+    retry_budget: Option<usize>,
+    frontier_limit: Option<usize>,
+    max_depth: Option<usize>,
+    empty_references: EmptyReferences,
+    disambiguation_seeds: DisambiguationSeeds,
+    on_progress: Option<Box<dyn Fn(ProgressEvent) + Send + Sync>>,
+    persist_path: Option<String>,
+    record_timings: bool,
+    time_limit: Option<(Duration, String)>,
+    rate_limit_markers: Option<Vec<String>>,
 }
+
 #[derive(Error, Debug)]
 pub enum CollectionErr {
     #[error("HTTP request failed.")]
     RequestError,
     #[error("Could not find path in given neighbourhood.")]
     PathFindingError,
+    #[error("Collection was cancelled.")]
+    Cancelled,
+    /// The cache has grown beyond the configured memory limit. The articles that were
+    /// collected so far are still available through Collector::cached_articles.
+    #[error("Estimated memory usage of {0} bytes exceeds the limit.")]
+    MemoryLimit(usize),
+    /// A request failed, but the retries of the whole crawl are used up already. This
+    /// contains the last error of the request.
+    #[error("Retry budget is exhausted, last error: {0}")]
+    RetryBudgetExhausted(FetchErr),
+    /// The search for a path has encountered more articles than allowed without reaching
+    /// the target. This contains the number of articles encountered.
+    #[error("Encountered {0} articles without finding a path.")]
+    FrontierTooLarge(usize),
+    /// The article has no references and EmptyReferences::Fail is configured.
+    #[error("Article {} has no references.", .0.get_name())]
+    NoReferences(URL),
+    /// The crawl took longer than allowed. This contains the path of the checkpoint it can
+    /// be resumed from, see Collector::resume_neighbourhood.
+    #[error("Time limit exceeded, checkpoint written to {0}.")]
+    TimeLimitExceeded(String),
+    /// A rate-limit page was served instead of the requested URL, even after backing off
+    /// as configured for FailureClass::TooManyRequests. See CollectorBuilder::rate_limit_markers.
+    #[error("Got a rate-limit page instead of {0}.")]
+    RateLimited(String),
+    /// The request for the given URL timed out, even after retrying as configured for
+    /// FailureClass::Connection. See CollectorBuilder::timeout.
+    #[error("Request for {0} timed out.")]
+    Timeout(String),
+    /// There is no path between articles on different Wikipedia domains.
+    #[error("Articles {0} and {1} are on different domains.")]
+    MixedDomains(URL, URL),
+    /// A seed is a disambiguation page and DisambiguationSeeds::Fail is configured.
+    #[error("Article {} is a disambiguation page, please pick a more specific one.", .0.get_name())]
+    DisambiguationSeed(URL),
 }
 
-impl Collector {
-    pub fn new() -> Self {
-        Collector {
-            cache: HashMap::new(),
-            processed: 0,
-            client: reqwest::Client::new(),
+/// How well the cache of a Collector has worked so far, see Collector::stats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CollectorStats {
+    /// The number of articles that were asked for, including the ones in lists. This is
+    /// always the sum of the hits and the misses.
+    pub lookups: usize,
+    /// The number of HTTP requests that were actually sent, including repetitions of
+    /// failed ones and the additional requests for content or continuations.
+    pub processed: usize,
+    /// The number of articles that were asked for and did not have to be requested, either
+    /// because they were cached or because they were in the same list more than once.
+    pub cache_hits: usize,
+    /// The number of articles that had to be requested, whether that succeeded or not.
+    pub cache_misses: usize,
+    /// The number of articles in the cache right now, counting every article once even if
+    /// it was also requested through a redirect.
+    pub cache_size: usize,
+}
+
+impl CollectorStats {
+    /// The share of the lookups that were cache hits, or 0 if there were none.
+    pub fn hit_rate(&self) -> f64 {
+        if self.lookups == 0 {
+            0.0
+        } else {
+            self.cache_hits as f64 / self.lookups as f64
         }
     }
+}
 
-    /// Takes a single URL and gets the corresponding articles. If this article has
-    /// been looked up before (by this particular object) the result is retreived from
-    /// a to limit the number of GET requests this program produces.
-    pub async fn get(&mut self, url: &URL) -> Result<Article, Box<dyn Error>> {
-        self.processed += 1;
-        if let Some(a) = self.cache.get(url) {
-            return Ok(a.clone());
-        }
-        let a = self.get_uncached(url).await?;
-        self.cache.insert(url.clone(), a.clone());
-        Ok(a)
+/// The result of looking for a path between two articles as it is produced by
+/// Collector::all_pairs_paths.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathResult {
+    pub from: URL,
+    pub to: URL,
+    /// The URLs of the articles along the path as returned by Collector::get_path.
+    pub path: Vec<URL>,
+}
+
+impl PathResult {
+    /// Whether this is the result for the given (ordered) pair of articles.
+    pub fn is_for(&self, from: &URL, to: &URL) -> bool {
+        self.from == *from && self.to == *to
     }
+}
 
-    /// A function to retrieve the HTML for a specific article by creating a HTTP get request.
-    /// The text is then parsed and a new Article object is created.
-    ///
-    /// Errors that can occur are mostly out of the users control as they are either related
-    /// to the I/O actions or to the content of the Wikipedia article which might not be possible
-    /// to parse. If this happens, the source code needs to be changed.
-    async fn get_uncached(&self, url: &URL) -> Result<Article, Box<dyn Error>> {
-        let r = self.client.get(&url.to_string()).send().await?;
-        let a = Article::parse(url.clone(), r.text().await?)?;
-        println!("{}", a.url.to_string());
-        Ok(a)
+/// A CancellationToken allows stopping a running batch of requests from the outside,
+/// e.g. from another task. All the clones of a token share the same state.
+///
+/// # Examples
+///
+/// ```
+/// use wglib::CancellationToken;
+///
+/// let token = CancellationToken::new();
+/// let handle = token.clone();
+/// handle.cancel();
+/// assert!(token.is_cancelled());
+/// ```
+#[derive(Clone)]
+pub struct CancellationToken {
+    sender: Arc<Mutex<Option<oneshot::Sender<()>>>>,
+    receiver: Shared<oneshot::Receiver<()>>,
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        CancellationToken::new()
     }
+}
 
-    /// Takes a vector of URLs and gets the corresponding articles. Note that the resulting
-    /// Vec<Article> is guranteed to have the results in the same order as the given Vec<URL>.
-    ///
-    /// This function does not make good use of concurrency as Collector::get is called for
-    /// each URL individually and consecutively. That means that each new HTTP request is only
-    /// made if the last one has received a response and has been parsed.
-    pub async fn get_list_stable(
-        &mut self,
-        urls: &Vec<URL>,
-    ) -> Result<Vec<Article>, Box<dyn Error>> {
-        eprint!("Getting list of {} urls... ", urls.len());
-        let mut res = Vec::new();
-        for x in urls {
-            res.push(self.get(x).await?);
+impl CancellationToken {
+    pub fn new() -> Self {
+        let (sender, receiver) = oneshot::channel();
+        CancellationToken {
+            sender: Arc::new(Mutex::new(Some(sender))),
+            receiver: receiver.shared(),
         }
-        eprintln!("Done");
-        Ok(res)
     }
 
-    /// Takes a vector of URLs and gets the corresponding articles. Note that the resulting
-    /// Vec<Article> is not guranteed to have the results in the same order as the given Vec<URL>.
-    ///
-    /// This function does make havy use of concurrency as the futures are obtained from Collector::get
-    /// for each and every URL and then they are joined and awaited. This leads to better usage of the
-    /// downtime due to I/O operations.
-    pub async fn get_list(&mut self, urls: &Vec<URL>) -> Result<Vec<Article>, Box<dyn Error>> {
-        eprint!("Getting list of {} urls... ", urls.len());
-        self.processed += urls.len();
-        let mut ys = Vec::new(); // Articles for all the inputs in urls
-        let mut fs = Vec::new(); // futures that have to be run because no values are cached
-        let mut xs = Vec::new(); // urls that have to be evaluated with corresponding articles in fs
-        for x in urls {
-            if let Some(y) = self.cache.get(x) {
-                ys.push(y.clone());
-            } else {
-                fs.push(self.get_uncached(x));
-                xs.push(x);
-            }
-        }
-        // We're awaiting all the futures at once to make use of the parallelism that's built in.
-        let res = futures::future::join_all(fs).await;
-        for r in xs.into_iter().zip(res) {
-            match r {
-                (x, Ok(y)) => {
-                    self.cache.insert(x.clone(), y.clone());
-                    ys.push(y);
-                }
-                (_, Err(e)) => {
-                    return Err(e);
-                }
-            }
+    /// Cancels everything that is using this token. Cancelling more than once has no
+    /// further effect.
+    pub fn cancel(&self) {
+        if let Some(s) = self.sender.lock().unwrap().take() {
+            // Nobody may be waiting, which is fine.
+            let _ = s.send(());
         }
-        eprintln!("Done");
-        Ok(ys)
     }
 
-    /// Gets all the neighbours of up to a given degree.
-    /// All values for the depth are valid as degree 0 means no neighbours are actually looked up.
-    pub async fn get_neighbourhood(
-        &mut self,
-        url: &URL,
-        depth: u32,
-    ) -> Result<Vec<Article>, Box<dyn Error>> {
-        let mut ts = HashSet::new(); // "Unhandled URLs"
-        let mut ns = HashSet::new(); // Encountered URLs
-        ts.insert(url.clone());
-        for _ in 1..depth {
-            eprintln!(
-                "Extending neighbourhood by {} ({} -> {})",
-                ts.len(),
-                ns.len(),
-                ns.len() + ts.len()
-            );
-            ns.extend(ts.iter().cloned());
-            let urls = ts.into_iter().collect();
-            let arts = self.get_list(&urls).await?;
-            let mut new_ts = HashSet::new();
-            for a in arts {
-                for u in a.references.iter().cloned() {
-                    if ns.insert(u.clone()) {
-                        // We only need to fetch this value if we've not seen it before.very
-                        new_ts.insert(u);
-                    }
-                }
-            }
-            eprintln!("New Ts: {} entries", new_ts.len());
-            ts = new_ts;
-        }
-        self.get_list(&ns.into_iter().collect()).await
+    pub fn is_cancelled(&self) -> bool {
+        self.sender.lock().unwrap().is_none()
     }
 
-    /// Given two URLs to valid Wikipedia articles this allows to find a chain of articles that
-    /// connects the two inputs by references.
-    pub async fn get_path(&mut self, og: &URL, tg: &URL) -> Result<Vec<Article>, Box<dyn Error>> {
-        let mut ts = HashSet::new(); // "Unhandled URLs"
-        let mut ns = HashSet::new(); // Encountered URLs
-        ts.insert(og.clone());
-        while !ts.contains(tg) {
-            ns.extend(ts.iter().cloned());
-            let arts = self.get_list(&ts.into_iter().collect()).await?;
-            let mut new_ts = HashSet::new();
-            for a in arts {
-                for u in a.references.iter().cloned() {
-                    if ns.insert(u.clone()) {
-                        new_ts.insert(u);
-                    }
-                }
-            }
-            ts = new_ts;
-        }
-        self.find_path(og, tg, ns.into_iter().collect()).await
+    /// Completes as soon as the token is cancelled.
+    pub async fn cancelled(&self) {
+        // The sender lives as long as self, so this only completes through Self::cancel.
+        let _ = self.receiver.clone().await;
     }
+}
 
-    /// Given a neighbourhood (i.e. a set, or rather a Vector, of URLs that are guranteed to contain a path between og and tg)
-    /// the path is found. It is extremely important that the given neighbourhood does indeed contain the desired path, otherwise the code may panic.
-    ///
-    /// # Panics
-    ///
-    /// 1. If the given set of nodes does not contain a valid path from og to tg.
-    /// 2. If either og or tg is not in the given set.
-    async fn find_path(
-        &mut self,
-        og: &URL,
-        tg: &URL,
-        mut ns: Vec<URL>,
-    ) -> Result<Vec<Article>, Box<dyn Error>> {
-        ns.sort();
-        let l = ns.len();
-        let mut adj = vec![false; l * l];
-        let mut seen = vec![false; l];
-        let og_idx = ns
-            .binary_search(&og)
-            .expect("Origin for required path is not in given neighbourhood.");
-        let tg_idx = ns
-            .binary_search(&tg)
-            .expect("Target for required path is not in given neighbourhood.");
-        let mut q = VecDeque::new();
-        q.push_back(og_idx);
-        seen[og_idx] = true;
-        while !seen[tg_idx] {
-            let v = q
-                .pop_front()
-                .expect("Target could not be visited before exhausting neighbourhood.");
-            let a = self.get(&ns[v]).await?;
-            for r in a.references {
-                if let Ok(k) = ns.binary_search(&r) {
-                    adj[l * v + k] = true; // Create edge v -> k
-                                           //eprintln!("Created edge {}->{}", ns[v].get_name(), ns[k].get_name());
-                    if !seen[k] {
-                        // If we've already seen this then we don't need to visit it again.
-                        q.push_back(k);
-                        seen[k] = true;
-                    }
-                }
+impl Drop for Collector {
+    fn drop(&mut self) {
+        if let Some(path) = self.persist_path.take() {
+            if let Err(e) = self.save_cache(&path) {
+                let error = e.to_string();
+                self.emit(ProgressEvent::CacheNotSaved { path, error });
             }
         }
-        let mut path = Vec::new();
-        let bd = binary_dijkstra(&adj, l, og_idx, tg_idx).unwrap();
-        for i in bd {
-            path.push(self.get(&ns[i]).await?);
-        }
-        Ok(path)
     }
 }
 
-/// A simplified (but naive) version of Dijkstra's algorithm to find a path in a directed graph
-/// given by an adjacency matrix without edge weights.
-///
-/// The main advantage of these constraints is that the first distance that is determined for any
-/// single node is guranteed to be the shortest distance as all the edges have the same length and
-/// any other node is at least as many steps away from the origin.
-fn binary_dijkstra(adj: &Vec<bool>, l: usize, og: usize, tg: usize) -> Option<Vec<usize>> {
-    if og >= l || tg >= l {
-        return None;
+impl Default for Collector {
+    fn default() -> Self {
+        Collector::new()
     }
-    if adj.len() != l * l {
-        // We require adj to be a "square" matrix.
-        return None;
+}
+
+impl CollectorBuilder {
+    /// Sets the Fetcher that is used to retrieve articles instead of the default HttpFetcher.
+    pub fn fetcher<F: Fetcher + 'static>(mut self, fetcher: F) -> Self {
+        self.fetcher = Some(Box::new(fetcher));
+        self
     }
-    let mut visited = vec![false; l]; // Whether or not a node has been processed, i.e. visited.
-    let mut from = vec![None; l]; // The neighbour the shortest path to a node comes from.
-    let mut dist = vec![0; l]; // "Tentative distance" of a node from og (note that it's either infinty, i.e. -1, or the actual distance)
-    let mut q = VecDeque::new(); // Queue of vertices to handle
-    dist[og] = 0;
-    visited[og] = true;
-    for n in neighs(adj, l, og) {
-        from[n] = Some(og);
-        q.push_back(n);
-    }
-    while dist[tg] < 0 && !q.is_empty() {
-        let v = q.pop_front().unwrap();
-        let p = from[v].unwrap();
-        dist[v] = dist[p] + 1;
-        visited[v] = true;
-        for n in neighs(adj, l, v) {
-            if let None = from[n] {
-                from[n] = Some(v);
-                q.push_back(n);
-            }
-        }
+
+    /// Sets all the options of the HTTP client at once. These are ignored if a
+    /// custom Fetcher is used.
+    pub fn http_options(mut self, opts: HttpOptions) -> Self {
+        self.http = opts;
+        self
     }
-    let mut path = Vec::new();
-    let mut v = tg;
-    while v != og {
-        path.push(v);
-        v = from[v].unwrap();
+
+    /// Sets whether HTTP/2 is used without negotiating it first.
+    pub fn http2_prior_knowledge(mut self, enabled: bool) -> Self {
+        self.http.http2_prior_knowledge = enabled;
+        self
     }
-    path.reverse();
-    Some(path)
-}
 
-/// Returns the indices of the neighbours for any node in a graph given by and adjacency matrix.
-///
-/// This function is potentially unsafe as it does not check the size of the adjacency matrix and
-/// may therefore try to access indices out of bounds.
-fn neighs(adj: &Vec<bool>, l: usize, v: usize) -> Vec<usize> {
-    let mut ns = Vec::new();
-    for i in 0..l {
-        if adj[l * v + i] {
-            ns.push(i);
-        }
+    /// Sets how long idle connections are kept alive for reuse.
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.http.pool_idle_timeout = Some(timeout);
+        self
     }
-    ns
-}
 
-mod tests {
-    // For some reason rust gives warnings that these imports are unneeded. Removing them leads to compile time errors, though.
-    use super::{Collector, URL};
-    use std::error::Error;
+    /// Sets the interval of TCP keep-alive probes.
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.http.tcp_keepalive = Some(interval);
+        self
+    }
 
-    #[test]
-    fn get_is_deterministic() -> Result<(), Box<dyn Error>> {
-        let mut runtime = tokio::runtime::Builder::new()
-            .basic_scheduler()
-            .threaded_scheduler()
-            .enable_all()
-            .build()
-            .unwrap();
-        let u = URL::new("https://en.wikipedia.org/wiki/Wikipedia")?;
-        let mut c = Collector::new();
+    /// Sets whether Nagle's algorithm is disabled.
+    pub fn tcp_nodelay(mut self, enabled: bool) -> Self {
+        self.http.tcp_nodelay = enabled;
+        self
+    }
+
+    /// Sets how long to wait for the whole response to a request before it fails with
+    /// CollectionErr::Timeout, see `DEFAULT_TIMEOUT` for the default. Timed out requests
+    /// are retried like lost connections, see FailureClass::Connection.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.http.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the User-Agent header of every request, e.g.
+    /// `wikigraph/0.1 (contact@example.com)`. Wikimedia may block heavy crawls whose user
+    /// agent doesn't tell them who to contact. See `DEFAULT_USER_AGENT` for the default.
+    pub fn user_agent(mut self, agent: &str) -> Self {
+        self.http.user_agent = String::from(agent);
+        self
+    }
+
+    /// Sets a limit for the estimated memory used by the cache in bytes. Once it is
+    /// exceeded, collection stops with CollectionErr::MemoryLimit.
+    ///
+    /// This is only an approximation (see Article::estimated_size), but it helps to stop
+    /// a crawl on a constrained machine before it runs out of memory.
+    pub fn memory_limit(mut self, bytes: usize) -> Self {
+        self.memory_limit = Some(bytes);
+        self
+    }
+
+    /// Limits the cache to the given number of articles. Once there are more, the ones
+    /// that were used least recently are evicted, so long-running crawls don't keep
+    /// growing. Unlike CollectorBuilder::memory_limit, this never stops a crawl.
+    ///
+    /// Evicted articles are requested again when they are needed, so a capacity that is
+    /// smaller than a neighbourhood makes the crawl a lot slower. Articles that were not
+    /// saved (see Collector::save_cache) before they are evicted are kept aside until the
+    /// next save, so nothing is lost, but they still take up memory until then.
+    /// A capacity of 0 is taken as 1.
+    pub fn cache_capacity(mut self, articles: usize) -> Self {
+        self.cache_capacity = Some(articles.max(1));
+        self
+    }
+
+    /// Sets how the titles of referenced articles are compared. This should match the
+    /// behaviour of the wiki that is crawled.
+    pub fn case_sensitivity(mut self, case: CaseSensitivity) -> Self {
+        self.parse_options.case_sensitivity = case;
+        self
+    }
+
+    /// Sets how the links are found in the HTML of every fetched article.
+    pub fn parse_backend(mut self, backend: ParseBackend) -> Self {
+        self.parse_options.backend = backend;
+        self
+    }
+
+    /// Sets how many requests may be in flight at once, see Collector::set_concurrency.
+    pub fn concurrency(mut self, limit: usize) -> Self {
+        self.concurrency = Some(limit);
+        self
+    }
+
+    /// Makes the Collector send fewer requests at once while a lot of them fail. The
+    /// concurrency never exceeds the limit set through CollectorBuilder::concurrency.
+    pub fn backpressure(mut self, policy: Backpressure) -> Self {
+        self.backpressure = Some(policy);
+        self
+    }
+
+    /// Sets the concurrency, the backpressure and the retry policy according to a preset,
+    /// see Politeness for the values. Any of these that are set explicitly are kept, no
+    /// matter in which order the methods are called.
+    pub fn politeness(mut self, preset: Politeness) -> Self {
+        self.politeness = Some(preset);
+        self
+    }
+
+    /// Makes the Collector wait at least the given interval between the starts of two
+    /// requests, e.g. 200ms for at most five requests per second. Retries are requests,
+    /// too, so they wait for their turn as well.
+    ///
+    /// This holds for all the requests of the Collector together, including the ones that
+    /// Collector::get_list sends at once. The concurrency still limits how many requests
+    /// may be in flight at the same time, so whichever of the two is stricter wins: with
+    /// slow responses the concurrency is the bottleneck, with fast ones the rate limit.
+    pub fn rate_limit(mut self, interval: Duration) -> Self {
+        self.rate_limit = Some(interval);
+        self
+    }
+
+    /// Sets the options that are used to parse every fetched article.
+    pub fn parse_options(mut self, opts: ParseOptions) -> Self {
+        self.parse_options = opts;
+        self
+    }
+
+    /// Sets whether the links of every article are requested from the MediaWiki API instead
+    /// of being parsed from its HTML, see Article::from_api. This doesn't depend on the
+    /// layout of the pages, but articles with a lot of links take several requests and
+    /// the HTML cannot be kept.
+    pub fn links_api(mut self, enabled: bool) -> Self {
+        self.links_api = enabled;
+        self
+    }
+
+    /// Sets whether the plain text of every article is fetched as well and stored in
+    /// Article::content. This needs an additional request per article, so it's off by default.
+    pub fn with_content(mut self, enabled: bool) -> Self {
+        self.with_content = enabled;
+        self
+    }
+
+    /// Sets whether the HTML of every article is kept in Article::html, so the articles
+    /// can be parsed again later without fetching them, see Collector::reparse_all. The
+    /// HTML is a lot larger than the references, so it's off by default.
+    pub fn retain_html(mut self, enabled: bool) -> Self {
+        self.retain_html = enabled;
+        self
+    }
+
+    /// Sets the number of references an article needs to have for them to be followed
+    /// while searching neighbourhoods and paths. Articles with fewer references, e.g. stubs,
+    /// are still collected, but the search does not continue through them. The articles
+    /// the search starts at are always expanded.
+    ///
+    /// By default every article is expanded.
+    pub fn min_out_degree(mut self, degree: usize) -> Self {
+        self.min_out_degree = degree;
+        self
+    }
+
+    /// Sets how often a request is repeated if it fails with a transient error (see
+    /// FetchErr::is_transient) and how long to wait before every repetition, no matter
+    /// what kind of error it is. See RetryPolicy::uniform.
+    ///
+    /// By default requests are repeated `DEFAULT_RETRIES` times with an exponential
+    /// backoff, see CollectorBuilder::retry_policy.
+    pub fn retries(self, retries: usize, delay: Duration) -> Self {
+        self.retry_policy(RetryPolicy::uniform(retries, delay))
+    }
+
+    /// Sets how failed requests are repeated depending on the kind of failure, e.g.
+    /// RetryPolicy::recommended.
+    ///
+    /// By default every transient failure is repeated `DEFAULT_RETRIES` times, the first
+    /// time after `DEFAULT_RETRY_DELAY` and then doubling the delay every time, see
+    /// RetryPolicy::exponential. Pass RetryPolicy::default to fail right away instead.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Sets how many retries may be made in total by all the requests of the Collector.
+    /// Once they are used up, the next failing request stops the crawl with
+    /// CollectionErr::RetryBudgetExhausted instead of being retried.
+    ///
+    /// This keeps a widespread outage from multiplying the load. By default there is no
+    /// limit other than the retries per request.
+    pub fn retry_budget(mut self, retries: usize) -> Self {
+        self.retry_budget = Some(retries);
+        self
+    }
+
+    /// Sets the number of articles a search for a path may encounter before it gives up
+    /// with CollectionErr::FrontierTooLarge. This protects against running out of memory
+    /// when two articles are very far apart. By default there is no limit.
+    pub fn frontier_limit(mut self, articles: usize) -> Self {
+        self.frontier_limit = Some(articles);
+        self
+    }
+
+    /// Sets the number of steps a search for a path may take, i.e. the length of the
+    /// longest path that can be found. If the target is further away, the search gives
+    /// up with CollectionErr::PathFindingError instead of crawling on. By default there is
+    /// no limit.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Sets what happens to articles without any references. By default they are kept.
+    pub fn empty_references(mut self, policy: EmptyReferences) -> Self {
+        self.empty_references = policy;
+        self
+    }
+
+    /// Sets what happens when a seed of a crawl is a disambiguation page.
+    pub fn disambiguation_seeds(mut self, policy: DisambiguationSeeds) -> Self {
+        self.disambiguation_seeds = policy;
+        self
+    }
+
+    /// Sets a function that is called with every ProgressEvent of a crawl, e.g. to show a
+    /// progress bar. By default the events are dropped and nothing is printed.
+    ///
+    /// The function may be called from several requests at once, so it should be quick.
+    pub fn on_progress<F: Fn(ProgressEvent) + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.on_progress = Some(Box::new(f));
+        self
+    }
+
+    /// Makes the Collector write its new articles to the given cache file when it is
+    /// dropped, see Collector::save_cache. Errors can't be returned from there, so they are
+    /// reported as ProgressEvent::CacheNotSaved through the callback of
+    /// CollectorBuilder::on_progress instead.
+    ///
+    /// Writing the file blocks whatever drops the Collector, which is why this has to be
+    /// enabled explicitly.
+    pub fn persist_on_drop(mut self, path: &str) -> Self {
+        self.persist_path = Some(String::from(path));
+        self
+    }
+
+    /// Sets whether the time spent in the different phases of a crawl is recorded, see
+    /// Collector::timings.
+    pub fn record_timings(mut self, enabled: bool) -> Self {
+        self.record_timings = enabled;
+        self
+    }
+
+    /// Limits how long a neighbourhood is crawled. If the limit is reached, the crawl
+    /// stops with CollectionErr::TimeLimitExceeded after writing a checkpoint to the given
+    /// path, which contains all the articles collected so far and the state of the
+    /// search. The crawl can then be continued by Collector::resume_neighbourhood.
+    ///
+    /// The limit applies to every call separately, so a resumed crawl gets the same time
+    /// again (and writes a new checkpoint if it runs out of time as well).
+    pub fn time_limit(mut self, limit: Duration, checkpoint: &str) -> Self {
+        self.time_limit = Some((limit, String::from(checkpoint)));
+        self
+    }
+
+    /// Sets the markers of pages that are served instead of articles when too many
+    /// requests are sent. Such pages are treated like a response with status 429, i.e.
+    /// retried with the backoff for FailureClass::TooManyRequests, and finally fail with
+    /// CollectionErr::RateLimited. The default markers are `RATE_LIMIT_MARKERS`.
+    pub fn rate_limit_markers(mut self, markers: &[&str]) -> Self {
+        self.rate_limit_markers = Some(markers.iter().map(|m| String::from(*m)).collect());
+        self
+    }
+
+    /// Sets whether the links in the reference section of an article and everything after
+    /// it are left out, see ParseOptions::ignore_reference_section. These are a lot of
+    /// links that are only loosely related to the article, so leaving them out makes paths
+    /// follow the ideas of the articles more closely. By default they are kept.
+    pub fn ignore_reference_section(mut self, enabled: bool) -> Self {
+        self.parse_options.ignore_reference_section = enabled;
+        self
+    }
+
+    /// Sets whether rejected links are recorded in Article::dropped.
+    pub fn record_dropped_links(mut self, record: bool) -> Self {
+        self.parse_options.record_dropped = record;
+        self
+    }
+
+    pub fn build(self) -> Collector {
+        let preset = self.politeness;
+        let concurrency = self
+            .concurrency
+            .or_else(|| preset.map(Politeness::concurrency))
+            .unwrap_or(DEFAULT_CONCURRENCY)
+            .max(1);
+        let backpressure = self
+            .backpressure
+            .or_else(|| preset.and_then(Politeness::backpressure));
+        Collector {
+            cache: HashMap::new(),
+            redirects: HashMap::new(),
+            cache_size: 0,
+            dirty: HashSet::new(),
+            unsaved: HashMap::new(),
+            memory_limit: self.memory_limit,
+            cache_capacity: self.cache_capacity,
+            last_used: HashMap::new(),
+            lru: BTreeMap::new(),
+            tick: 0,
+            lookups: 0,
+            cache_hits: 0,
+            processed: AtomicUsize::new(0),
+            fetcher: match self.fetcher {
+                Some(f) => f,
+                None => Box::new(HttpFetcher::with_options(self.http)),
+            },
+            concurrency,
+            throttle: backpressure.map(|b| Throttle::new(b, concurrency)),
+            rate_limiter: self.rate_limit.map(RateLimiter::new),
+            parse_options: self.parse_options,
+            links_api: self.links_api,
+            with_content: self.with_content,
+            retain_html: self.retain_html,
+            min_out_degree: self.min_out_degree,
+            retry_policy: self
+                .retry_policy
+                .or_else(|| preset.map(Politeness::retry_policy))
+                .unwrap_or_else(|| RetryPolicy::exponential(DEFAULT_RETRIES, DEFAULT_RETRY_DELAY)),
+            retry_budget: self.retry_budget.map(AtomicUsize::new),
+            frontier_limit: self.frontier_limit,
+            max_depth: self.max_depth,
+            empty_references: self.empty_references,
+            disambiguation_seeds: self.disambiguation_seeds,
+            on_progress: self.on_progress,
+            persist_path: self.persist_path,
+            timings: if self.record_timings {
+                Some(Timings::new())
+            } else {
+                None
+            },
+            time_limit: self.time_limit,
+            rate_limit_markers: self.rate_limit_markers.unwrap_or_else(|| {
+                RATE_LIMIT_MARKERS
+                    .iter()
+                    .map(|m| String::from(*m))
+                    .collect()
+            }),
+        }
+    }
+}
+
+impl Collector {
+    pub fn new() -> Self {
+        Collector::builder().build()
+    }
+
+    pub fn builder() -> CollectorBuilder {
+        CollectorBuilder::default()
+    }
+
+    /// Creates a Collector that sends at most the given number of requests at once.
+    pub fn with_concurrency(limit: usize) -> Self {
+        Collector::builder().concurrency(limit).build()
+    }
+
+    /// Creates a Collector that gets its pages from the given Fetcher instead of
+    /// Wikipedia, e.g. to serve canned pages in tests.
+    pub fn with_fetcher<F: Fetcher + 'static>(fetcher: F) -> Self {
+        Collector::builder().fetcher(fetcher).build()
+    }
+
+    /// Sets how many requests Collector::get_list and everything that builds on it may
+    /// have in flight at once. Sending a lot more than `DEFAULT_CONCURRENCY` requests at
+    /// once is likely to get the crawl rate-limited or banned. A limit of 0 is taken as 1.
+    pub fn set_concurrency(&mut self, limit: usize) {
+        self.concurrency = limit.max(1);
+        if let Some(t) = &self.throttle {
+            self.throttle = Some(Throttle::new(t.policy(), self.concurrency));
+        }
+    }
+
+    /// The number of requests that may currently be in flight at once. This is only less
+    /// than the configured concurrency while CollectorBuilder::backpressure slows down.
+    pub fn concurrency_limit(&self) -> usize {
+        match &self.throttle {
+            Some(t) => t.limit().min(self.concurrency),
+            None => self.concurrency,
+        }
+    }
+
+    /// Takes a single URL and gets the corresponding articles. If this article has
+    /// been looked up before (by this particular object) the result is retreived from
+    /// a to limit the number of GET requests this program produces.
+    pub async fn get(&mut self, url: &URL) -> Result<Article, Box<dyn Error>> {
+        self.lookups += 1;
+        if let Some(a) = self.cache.get(url).cloned() {
+            self.cache_hits += 1;
+            self.touch(&a.url);
+            return Ok(a);
+        }
+        let a = self.get_uncached(url).await?;
+        self.cache_insert(url.clone(), a.clone());
+        self.check_memory()?;
+        Ok(a)
+    }
+
+    /// Returns how many articles were asked for so far, how many of them came from the
+    /// cache and how many requests were sent for the others.
+    pub fn stats(&self) -> CollectorStats {
+        CollectorStats {
+            lookups: self.lookups,
+            processed: self.processed.load(Ordering::SeqCst),
+            cache_hits: self.cache_hits,
+            cache_misses: self.lookups - self.cache_hits,
+            cache_size: self.cache.iter().filter(|(u, a)| **u == a.url).count(),
+        }
+    }
+
+    /// Returns copies of all the articles that have been collected so far.
+    ///
+    /// Every article is only returned once, even if it was also requested through a
+    /// redirect, and references to known redirects point to their targets instead.
+    pub fn cached_articles(&self) -> Vec<Article> {
+        let arts = self
+            .cache
+            .iter()
+            .filter(|(u, a)| **u == a.url)
+            .map(|(_, a)| a.clone())
+            .collect();
+        self.collapse_redirects(arts)
+    }
+
+    /// The number of redirects that were requested and resolved to the article they point
+    /// to, e.g. `/wiki/USA` to `/wiki/United_States`. Such articles are cached under both
+    /// URLs, so either of them leads to the same Article.
+    pub fn redirects_followed(&self) -> usize {
+        self.redirects.len()
+    }
+
+    /// Removes the articles that are in the list more than once because they were also
+    /// requested through a redirect and makes references to redirects point to their
+    /// targets, so every article is a single node.
+    fn collapse_redirects(&self, mut arts: Vec<Article>) -> Vec<Article> {
+        let mut seen = HashSet::new();
+        arts.retain(|a| seen.insert(a.url.clone()));
+        if self.redirects.is_empty() {
+            return arts;
+        }
+        for a in arts.iter_mut() {
+            if a.references.iter().any(|r| self.redirects.contains_key(r)) {
+                a.references = a
+                    .references
+                    .drain()
+                    .map(|r| self.redirects.get(&r).cloned().unwrap_or(r))
+                    .collect();
+            }
+        }
+        arts
+    }
+
+    /// The time spent in the different phases of the crawl so far, if it is recorded. See
+    /// CollectorBuilder::record_timings.
+    pub fn timings(&self) -> Option<&Timings> {
+        self.timings.as_ref()
+    }
+
+    fn record_since(&self, phase: &'static str, start: Instant) {
+        if let Some(t) = &self.timings {
+            t.record_since(phase, start);
+        }
+    }
+
+    /// The estimated memory used by the cache in bytes. See Article::estimated_size.
+    pub fn estimated_memory(&self) -> usize {
+        self.cache_size
+    }
+
+    /// Writes all the articles that have been collected since the last save (or load) to
+    /// the given cache file. Articles that are already in the file are not written again,
+    /// which makes it cheap to save frequently. Returns the number of articles written.
+    ///
+    /// See `cache::append_articles` for the format of the file. An interrupted save only
+    /// cuts off the last line, which is skipped when the file is loaded.
+    pub fn save_cache<P: AsRef<Path>>(&mut self, path: P) -> Result<usize, Box<dyn Error>> {
+        // Articles that were requested through a redirect are written only once.
+        let mut dirty: Vec<_> = self
+            .dirty
+            .iter()
+            .filter(|u| !self.redirects.contains_key(*u))
+            .map(|u| &self.cache[u])
+            .chain(self.unsaved.values())
+            .collect();
+        dirty.sort_by(|a, b| a.url.cmp(&b.url));
+        cache::append_articles(path, &dirty)?;
+        let n = dirty.len();
+        self.dirty.clear();
+        self.unsaved.clear();
+        Ok(n)
+    }
+
+    /// Adds all the articles from the given cache file to the cache, so they don't have
+    /// to be requested again. Returns the number of articles that were read.
+    ///
+    /// A missing file is treated like an empty one, so the same path can be used from the
+    /// first run on. Lines that are not valid articles are skipped.
+    pub fn load_cache<P: AsRef<Path>>(&mut self, path: P) -> Result<usize, Box<dyn Error>> {
+        let (arts, invalid) = match cache::read_articles(&path) {
+            Ok(r) => r,
+            Err(e) => match e.downcast_ref::<std::io::Error>() {
+                Some(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+                _ => return Err(e),
+            },
+        };
+        if invalid > 0 {
+            self.emit(ProgressEvent::InvalidCacheEntries {
+                path: path.as_ref().display().to_string(),
+                skipped: invalid,
+            });
+        }
+        let n = arts.len();
+        for a in arts {
+            let url = a.url.clone();
+            self.cache_insert(url.clone(), a);
+            self.dirty.remove(&url);
+        }
+        self.check_memory()?;
+        Ok(n)
+    }
+
+    /// Parses all the cached articles whose HTML was kept again with the current
+    /// ParseOptions, so the references (as well as the categories and dropped links) are
+    /// updated without fetching anything. This is useful after the parser was improved,
+    /// e.g. when reading an old cache file. Returns the number of articles that were parsed.
+    ///
+    /// See CollectorBuilder::retain_html.
+    pub fn reparse_all(&mut self) -> Result<usize, Box<dyn Error>> {
+        // Articles that were requested through a redirect are only parsed once.
+        let mut urls: Vec<URL> = self
+            .cache
+            .iter()
+            .filter(|(u, a)| **u == a.url && a.html.is_some())
+            .map(|(u, _)| u.clone())
+            .collect();
+        urls.sort();
+        let mut aliases: HashMap<URL, Vec<URL>> = HashMap::new();
+        for (alias, target) in self.redirects.iter() {
+            aliases
+                .entry(target.clone())
+                .or_default()
+                .push(alias.clone());
+        }
+        for u in urls.iter() {
+            let old = &self.cache[u];
+            let html = old.html.clone().unwrap();
+            let mut a = Article::parse_with(old.url.clone(), html.clone(), &self.parse_options)?;
+            a.content = old.content.clone();
+            a.html = Some(html);
+            // The redirects are known already, so they only have to see the new article.
+            for alias in aliases.get(u).into_iter().flatten() {
+                if let Some(entry) = self.cache.get_mut(alias) {
+                    *entry = a.clone();
+                }
+            }
+            self.cache_insert(u.clone(), a);
+        }
+        self.check_memory()?;
+        Ok(urls.len())
+    }
+
+    fn cache_insert(&mut self, url: URL, a: Article) {
+        if url != a.url {
+            // The article was requested through a redirect, so it is cached under its
+            // own URL as well.
+            self.redirects.insert(url.clone(), a.url.clone());
+            self.cache_insert(a.url.clone(), a.clone());
+        }
+        self.dirty.insert(url.clone());
+        // Only the canonical entry counts, the redirects are just another way to find it.
+        let canonical = url == a.url;
+        if canonical {
+            self.cache_size += a.estimated_size();
+        }
+        if let Some(old) = self.cache.insert(url.clone(), a) {
+            if canonical {
+                self.cache_size -= old.estimated_size();
+            }
+        }
+        if canonical {
+            // A newer version of an evicted article replaces the one that wasn't saved.
+            self.unsaved.remove(&url);
+            self.touch(&url);
+            self.evict();
+        }
+    }
+
+    /// Removes all the articles from the cache, including the ones that were not saved
+    /// yet. Known redirects are kept.
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+        self.cache_size = 0;
+        self.dirty.clear();
+        self.unsaved.clear();
+        self.last_used.clear();
+        self.lru.clear();
+    }
+
+    /// Marks an article as used just now, see CollectorBuilder::cache_capacity.
+    fn touch(&mut self, url: &URL) {
+        if self.cache_capacity.is_none() {
+            return;
+        }
+        self.tick += 1;
+        if let Some(t) = self.last_used.insert(url.clone(), self.tick) {
+            self.lru.remove(&t);
+        }
+        self.lru.insert(self.tick, url.clone());
+    }
+
+    /// Evicts the least recently used articles until the cache is within its capacity.
+    /// The redirects to an article are evicted together with it.
+    fn evict(&mut self) {
+        let cap = match self.cache_capacity {
+            Some(c) => c,
+            None => return,
+        };
+        while self.last_used.len() > cap {
+            let (_, url) = match self.lru.pop_first() {
+                Some(e) => e,
+                None => return,
+            };
+            self.last_used.remove(&url);
+            let aliases = self
+                .redirects
+                .iter()
+                .filter(|(_, t)| **t == url)
+                .map(|(a, _)| a.clone());
+            for u in aliases
+                .chain(std::iter::once(url.clone()))
+                .collect::<Vec<_>>()
+            {
+                let a = self.cache.remove(&u);
+                let dirty = self.dirty.remove(&u);
+                if let Some(a) = a.filter(|_| u == url) {
+                    self.cache_size -= a.estimated_size();
+                    if dirty {
+                        // It still has to be written by the next save.
+                        self.unsaved.insert(u, a);
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_memory(&self) -> Result<(), CollectionErr> {
+        match self.memory_limit {
+            Some(l) if self.cache_size > l => Err(CollectionErr::MemoryLimit(self.cache_size)),
+            _ => Ok(()),
+        }
+    }
+
+    /// A function to retrieve the HTML for a specific article by creating a HTTP get request
+    /// through the Fetcher. The text is then parsed and a new Article object is created.
+    ///
+    /// Errors that can occur are mostly out of the users control as they are either related
+    /// to the I/O actions or to the content of the Wikipedia article which might not be possible
+    /// to parse. If this happens, the source code needs to be changed.
+    async fn get_uncached(&self, url: &URL) -> Result<Article, Box<dyn Error>> {
+        let mut a = if self.links_api {
+            self.get_from_api(url).await?
+        } else {
+            self.get_from_html(url).await?
+        };
+        if self.with_content {
+            let start = Instant::now();
+            let extract = self.fetch(&Article::extract_url(url)).await?;
+            self.record_since(timing::PHASE_FETCH, start);
+            a.content = Article::parse_extract(&extract)?;
+        }
+        if a.references.is_empty() && self.empty_references == EmptyReferences::Fail {
+            return Err(Box::new(CollectionErr::NoReferences(a.url)));
+        }
+        self.emit(ProgressEvent::Fetched(a.url.clone()));
+        Ok(a)
+    }
+
+    async fn get_from_html(&self, url: &URL) -> Result<Article, Box<dyn Error>> {
+        let start = Instant::now();
+        let text = self.fetch(&url.to_string()).await?;
+        self.record_since(timing::PHASE_FETCH, start);
+        let html = if self.retain_html {
+            Some(text.clone())
+        } else {
+            None
+        };
+        let start = Instant::now();
+        let case = self.parse_options.case_sensitivity;
+        let target = Article::redirect_target(url, &text, case);
+        let mut a = Article::parse_with(url.clone(), text, &self.parse_options)?;
+        self.record_since(timing::PHASE_PARSE, start);
+        a.html = html;
+        if let Some(t) = target {
+            a.url = t;
+        }
+        Ok(a)
+    }
+
+    /// Requests the links of an article from the MediaWiki API, following the
+    /// continuations until all of them are there. See CollectorBuilder::links_api.
+    async fn get_from_api(&self, url: &URL) -> Result<Article, Box<dyn Error>> {
+        let mut responses = Vec::new();
+        let mut cont = Vec::new();
+        loop {
+            let start = Instant::now();
+            let r = self.fetch(&Article::links_url(url, &cont)).await?;
+            self.record_since(timing::PHASE_FETCH, start);
+            let next = Article::api_continuation(&r)?;
+            responses.push(r);
+            match next {
+                Some(c) => cont = c,
+                None => break,
+            }
+        }
+        let start = Instant::now();
+        let responses: Vec<&str> = responses.iter().map(String::as_str).collect();
+        let a = Article::from_api_with(url.clone(), &responses, &self.parse_options)?;
+        self.record_since(timing::PHASE_PARSE, start);
+        Ok(a)
+    }
+
+    /// Whether the given page was served instead of an article because of too many
+    /// requests, see CollectorBuilder::rate_limit_markers.
+    fn is_rate_limited(&self, page: &str) -> bool {
+        self.rate_limit_markers
+            .iter()
+            .any(|m| page.contains(m.as_str()))
+    }
+
+    fn record_outcome(&self, failed: bool) {
+        if let Some(t) = &self.throttle {
+            t.record(failed);
+        }
+    }
+
+    fn emit(&self, event: ProgressEvent) {
+        if let Some(f) = &self.on_progress {
+            f(event);
+        }
+    }
+
+    /// Gets the seeds of a crawl and checks that none of them is a disambiguation page,
+    /// see CollectorBuilder::disambiguation_seeds.
+    async fn check_seeds(&mut self, seeds: &[URL]) -> Result<(), Box<dyn Error>> {
+        for a in self.get_list(seeds).await? {
+            self.check_seed(&a)?;
+        }
+        Ok(())
+    }
+
+    /// Checks a single seed that was received already, see Collector::check_seeds.
+    fn check_seed(&self, a: &Article) -> Result<(), Box<dyn Error>> {
+        if !a.is_disambiguation() {
+            return Ok(());
+        }
+        match self.disambiguation_seeds {
+            DisambiguationSeeds::Warn => {
+                self.emit(ProgressEvent::DisambiguationSeed(a.url.clone()));
+                Ok(())
+            }
+            DisambiguationSeeds::Fail => {
+                Err(Box::new(CollectionErr::DisambiguationSeed(a.url.clone())))
+            }
+        }
+    }
+
+    /// Whether the given article is part of the results of a list, see EmptyReferences::Drop.
+    fn is_listed(&self, a: &Article) -> bool {
+        self.empty_references != EmptyReferences::Drop || !a.references.is_empty()
+    }
+
+    /// Sends a request through the Fetcher and repeats it as configured through
+    /// CollectorBuilder::retry_policy and CollectorBuilder::retry_budget.
+    ///
+    /// Rate-limit pages are retried like responses with status 429, see
+    /// CollectorBuilder::rate_limit_markers.
+    async fn fetch(&self, url: &str) -> Result<String, Box<dyn Error>> {
+        let mut attempt = 0;
+        loop {
+            let permit = match &self.throttle {
+                Some(t) => Some(t.acquire().await),
+                None => None,
+            };
+            if let Some(r) = &self.rate_limiter {
+                r.wait().await;
+            }
+            self.processed.fetch_add(1, Ordering::SeqCst);
+            let res = self.fetcher.fetch(url).await;
+            drop(permit);
+            // A rate-limit page is the only failure without a FetchErr.
+            let (class, e) = match res {
+                Ok(text) if !self.is_rate_limited(&text) => {
+                    self.record_outcome(false);
+                    return Ok(text);
+                }
+                Ok(_) => (Some(FailureClass::TooManyRequests), None),
+                Err(e) => (e.class(), Some(e)),
+            };
+            self.record_outcome(class.is_some());
+            let backoff = match class.and_then(|c| self.retry_policy.backoff(c)) {
+                Some(b) if attempt < b.retries => b,
+                _ => {
+                    return Err(match e {
+                        Some(FetchErr::Timeout) => {
+                            Box::new(CollectionErr::Timeout(String::from(url)))
+                        }
+                        Some(e) => Box::new(e),
+                        None => Box::new(CollectionErr::RateLimited(String::from(url))),
+                    })
+                }
+            };
+            if let Some(budget) = &self.retry_budget {
+                let take = |b: usize| b.checked_sub(1);
+                if budget
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, take)
+                    .is_err()
+                {
+                    return Err(Box::new(match e {
+                        Some(e) => CollectionErr::RetryBudgetExhausted(e),
+                        None => CollectionErr::RateLimited(String::from(url)),
+                    }));
+                }
+            }
+            attempt += 1;
+            tokio::time::delay_for(backoff.delay_before(attempt - 1)).await;
+        }
+    }
+
+    /// Takes a vector of URLs and gets the corresponding articles. Note that the resulting
+    /// Vec<Article> is guranteed to have the results in the same order as the given Vec<URL>.
+    ///
+    /// This function does not make good use of concurrency as Collector::get is called for
+    /// each URL individually and consecutively. That means that each new HTTP request is only
+    /// made if the last one has received a response and has been parsed.
+    pub async fn get_list_stable(&mut self, urls: &[URL]) -> Result<Vec<Article>, Box<dyn Error>> {
+        let mut res = Vec::new();
+        let mut cache_hits = 0;
+        for x in urls {
+            if self.cache.contains_key(x) {
+                cache_hits += 1;
+            }
+            let a = self.get(x).await?;
+            if self.is_listed(&a) {
+                res.push(a);
+            }
+        }
+        self.emit(ProgressEvent::ListCompleted {
+            requested: urls.len(),
+            cache_hits,
+        });
+        Ok(res)
+    }
+
+    /// Takes a vector of URLs and gets the corresponding articles. Note that the resulting
+    /// Vec<Article> is not guranteed to have the results in the same order as the given Vec<URL>.
+    ///
+    /// This function does make havy use of concurrency as the futures are obtained from Collector::get
+    /// for each and every URL and then awaited together, up to the limit set through
+    /// Collector::set_concurrency at once. This leads to better usage of the downtime due to I/O
+    /// operations.
+    ///
+    /// Dropping the returned future cancels all the requests that are still outstanding.
+    pub async fn get_list(&mut self, urls: &[URL]) -> Result<Vec<Article>, Box<dyn Error>> {
+        self.get_list_cancellable(urls, &CancellationToken::new())
+            .await
+    }
+
+    /// Does the same as Collector::get_list, but stops as soon as the given token is
+    /// cancelled. In that case all the outstanding requests are dropped and
+    /// CollectionErr::Cancelled is returned.
+    ///
+    /// Articles that were received before the cancellation (or before an error) are
+    /// still cached, so no work is lost when the batch is retried.
+    pub async fn get_list_cancellable(
+        &mut self,
+        urls: &[URL],
+        token: &CancellationToken,
+    ) -> Result<Vec<Article>, Box<dyn Error>> {
+        Ok(self.collect_list(urls, token, false).await?.0)
+    }
+
+    /// Does the same as Collector::get_list, but doesn't stop if some of the articles can't
+    /// be fetched, e.g. because they don't exist. Instead, those URLs are returned together
+    /// with their errors, next to the articles that could be fetched.
+    ///
+    /// Errors that concern the whole crawl still stop it, i.e. CollectionErr::MemoryLimit
+    /// and CollectionErr::RetryBudgetExhausted.
+    pub async fn get_list_lenient(
+        &mut self,
+        urls: &[URL],
+    ) -> Result<(Vec<Article>, Vec<(URL, Box<dyn Error>)>), Box<dyn Error>> {
+        self.collect_list(urls, &CancellationToken::new(), true)
+            .await
+    }
+
+    /// Gets the articles for Collector::get_list_cancellable and Collector::get_list_lenient.
+    /// If `lenient` is set, the errors of single articles are returned next to the articles
+    /// instead of stopping everything.
+    async fn collect_list(
+        &mut self,
+        urls: &[URL],
+        token: &CancellationToken,
+        lenient: bool,
+    ) -> Result<(Vec<Article>, Vec<(URL, Box<dyn Error>)>), Box<dyn Error>> {
+        self.lookups += urls.len();
+        let mut ys = Vec::new(); // Articles for all the inputs in urls
+        let mut missing = Vec::new(); // urls that have to be requested because no values are cached
+        let mut pending = HashSet::new(); // urls that are requested by one of the futures
+        let mut repeated = Vec::new(); // urls that occur more than once and are only requested once
+        for x in urls {
+            if let Some(y) = self.cache.get(x) {
+                ys.push(y.clone());
+            } else if !pending.insert(x) {
+                repeated.push(x);
+            } else {
+                missing.push(x);
+            }
+        }
+        let cache_hits = ys.len();
+        self.cache_hits += urls.len() - missing.len();
+        if self.cache_capacity.is_some() {
+            for u in ys.iter().map(|y| y.url.clone()).collect::<Vec<_>>() {
+                self.touch(&u);
+            }
+        }
+        let this = &*self;
+        // Up to `concurrency` futures are polled at once to make use of the parallelism
+        // that's built in without flooding the server.
+        let mut fs = stream::iter(missing)
+            .map(|x| async move { (x, this.get_uncached(x).await) })
+            .buffer_unordered(self.concurrency);
+        let mut done = Vec::new(); // urls and articles that have been received
+        let mut failed = Vec::new(); // urls that could not be received if lenient
+        let mut failure = None;
+        loop {
+            let cancelled = token.cancelled();
+            futures::pin_mut!(cancelled);
+            match future::select(fs.next(), cancelled).await {
+                Either::Left((Some((x, Ok(y))), _)) => done.push((x, y)),
+                Either::Left((Some((x, Err(e))), _)) => {
+                    let fatal = matches!(
+                        e.downcast_ref(),
+                        Some(CollectionErr::RetryBudgetExhausted(_))
+                    );
+                    if lenient && !fatal {
+                        failed.push((x.clone(), e));
+                        continue;
+                    }
+                    failure = Some(e);
+                    break;
+                }
+                Either::Left((None, _)) => break,
+                Either::Right(_) => {
+                    failure = Some(Box::new(CollectionErr::Cancelled) as Box<dyn Error>);
+                    break;
+                }
+            }
+        }
+        // Anything that is still running is cancelled here.
+        drop(fs);
+        // The cache may evict articles, so the repeated urls are looked up in here.
+        let mut received = HashMap::new();
+        for (x, y) in done {
+            self.cache_insert(x.clone(), y.clone());
+            received.insert(x, ys.len());
+            ys.push(y);
+        }
+        if let Some(e) = failure {
+            return Err(e);
+        }
+        // Repeated urls that failed are only reported once.
+        let repeated: Vec<_> = repeated
+            .into_iter()
+            .filter_map(|x| received.get(x).map(|i| ys[*i].clone()))
+            .collect();
+        ys.extend(repeated);
+        self.check_memory()?;
+        ys.retain(|a| self.is_listed(a));
+        self.emit(ProgressEvent::ListCompleted {
+            requested: urls.len(),
+            cache_hits,
+        });
+        Ok((ys, failed))
+    }
+
+    /// Gets the article together with all the articles that are at most `depth` links
+    /// away from it, e.g. with depth 1 the article and the ones it references directly.
+    /// All values for the depth are valid as depth 0 means no neighbours are looked up,
+    /// i.e. only the article itself is returned.
+    pub async fn get_neighbourhood(
+        &mut self,
+        url: &URL,
+        depth: u32,
+    ) -> Result<Vec<Article>, Box<dyn Error>> {
+        self.get_union_neighbourhood(std::slice::from_ref(url), depth)
+            .await
+    }
+
+    /// Does the same as Collector::get_neighbourhood, but yields the articles one by one
+    /// as soon as they arrive, so they can be processed while the crawl goes on.
+    ///
+    /// The articles that are received are not cached, so the memory that is needed doesn't
+    /// grow with the neighbourhood, apart from the URLs that were seen. Articles that are
+    /// cached already are used, though. Redirects are only yielded once, under their
+    /// canonical URL, but references to them are not rewritten.
+    ///
+    /// The seed is checked just like for Collector::get_neighbourhood once it arrives, see
+    /// CollectorBuilder::disambiguation_seeds. The first error ends the stream.
+    pub fn get_neighbourhood_stream<'a>(
+        &'a self,
+        url: &URL,
+        depth: u32,
+    ) -> impl Stream<Item = Result<Article, Box<dyn Error>>> + 'a {
+        let seeds = vec![url.clone()];
+        let state = NeighbourhoodStream {
+            collector: self,
+            level: Some(self.level_stream(seeds.clone())),
+            seen: seeds.iter().cloned().collect(),
+            seeds,
+            next: HashSet::new(),
+            remaining: depth,
+            failed: false,
+        };
+        stream::unfold(state, |mut st| async move {
+            if st.failed {
+                return None;
+            }
+            loop {
+                let level = st.level.as_mut()?;
+                match level.next().await {
+                    Some((_, Err(e))) => {
+                        st.failed = true;
+                        return Some((Err(e), st));
+                    }
+                    Some((u, Ok(a))) => {
+                        if st.seeds.contains(&u) {
+                            if let Err(e) = st.collector.check_seed(&a) {
+                                st.failed = true;
+                                return Some((Err(e), st));
+                            }
+                        }
+                        // A redirect to an article that was seen already.
+                        if u != a.url && !st.seen.insert(a.url.clone()) {
+                            continue;
+                        }
+                        if st.remaining > 0 && st.collector.expands(&a, &st.seeds) {
+                            for u in a.references.iter() {
+                                if st.seen.insert(u.clone()) {
+                                    st.next.insert(u.clone());
+                                }
+                            }
+                        }
+                        if st.collector.is_listed(&a) {
+                            return Some((Ok(a), st));
+                        }
+                    }
+                    None if st.remaining == 0 || st.next.is_empty() => st.level = None,
+                    None => {
+                        st.remaining -= 1;
+                        let urls = std::mem::take(&mut st.next).into_iter().collect();
+                        st.level = Some(st.collector.level_stream(urls));
+                    }
+                }
+            }
+        })
+    }
+
+    /// Gets the given articles for Collector::get_neighbourhood_stream, from the cache if
+    /// possible, and yields them in the order they arrive next to the URLs they were
+    /// requested with.
+    fn level_stream(&self, urls: Vec<URL>) -> LevelStream<'_> {
+        stream::iter(urls)
+            .map(move |u| async move {
+                let a = match self.cache.get(&u) {
+                    Some(a) => Ok(a.clone()),
+                    None => self.get_uncached(&u).await,
+                };
+                (u, a)
+            })
+            .buffer_unordered(self.concurrency)
+            .boxed_local()
+    }
+
+    /// Gets the URLs of all the articles that link to the given one from the "What links
+    /// here" list of the MediaWiki API, which the article itself doesn't tell. Articles
+    /// with a lot of backlinks take several requests.
+    ///
+    /// Nothing is cached, so asking for the same article twice sends the requests again.
+    pub async fn get_reverse_links(&mut self, url: &URL) -> Result<HashSet<URL>, Box<dyn Error>> {
+        let case = self.parse_options.case_sensitivity;
+        let mut res = HashSet::new();
+        let mut cont = Vec::new();
+        loop {
+            let response = self.fetch(&Article::backlinks_url(url, &cont)).await?;
+            let (urls, next) = Article::parse_backlinks(&response, url.domain_config(), case)?;
+            res.extend(urls);
+            match next {
+                Some(c) => cont = c,
+                None => return Ok(res),
+            }
+        }
+    }
+
+    /// Gets the given articles and keeps only the references between them, i.e. shows how
+    /// the given articles are linked among each other. Nothing but the given articles is
+    /// requested.
+    pub async fn get_seed_subgraph(
+        &mut self,
+        urls: &[URL],
+    ) -> Result<Vec<Article>, Box<dyn Error>> {
+        let seeds: HashSet<&URL> = urls.iter().collect();
+        let mut arts = self.get_list(urls).await?;
+        for a in arts.iter_mut() {
+            a.references.retain(|r| seeds.contains(r));
+        }
+        Ok(arts)
+    }
+
+    /// Gets the combined neighbourhood of several URLs, i.e. every article that is in the
+    /// neighbourhood of at least one of them, each exactly once.
+    ///
+    /// This is the same as the union of the separate neighbourhoods, but all the URLs are
+    /// expanded together, so no article is requested more than once.
+    pub async fn get_union_neighbourhood(
+        &mut self,
+        urls: &[URL],
+        depth: u32,
+    ) -> Result<Vec<Article>, Box<dyn Error>> {
+        self.check_seeds(urls).await?;
+        let ts: HashSet<URL> = urls.iter().cloned().collect();
+        self.crawl_neighbourhood(urls, depth, ts, HashSet::new())
+            .await
+    }
+
+    /// Continues a neighbourhood crawl from a checkpoint that was written when the crawl
+    /// ran out of time, see CollectorBuilder::time_limit. The result is the same as if the
+    /// crawl had not been interrupted, and no article in the checkpoint is requested again.
+    pub async fn resume_neighbourhood(
+        &mut self,
+        path: &str,
+    ) -> Result<Vec<Article>, Box<dyn Error>> {
+        let cp = Checkpoint::load(path)?;
+        for a in cp.articles {
+            let url = a.url.clone();
+            self.cache_insert(url, a);
+        }
+        self.check_memory()?;
+        self.crawl_neighbourhood(
+            &cp.seeds,
+            cp.depth,
+            cp.frontier.into_iter().collect(),
+            cp.seen.into_iter().collect(),
+        )
+        .await
+    }
+
+    /// Expands the frontier `ts` by the given number of levels and gets everything that
+    /// was encountered on the way, i.e. `ns` and the frontiers. If there is a time limit
+    /// and it runs out, a checkpoint of the current state is written instead.
+    async fn crawl_neighbourhood(
+        &mut self,
+        urls: &[URL],
+        mut depth: u32,
+        mut ts: HashSet<URL>, // "Unhandled URLs"
+        mut ns: HashSet<URL>, // Encountered URLs
+    ) -> Result<Vec<Article>, Box<dyn Error>> {
+        let deadline = self
+            .time_limit
+            .as_ref()
+            .map(|(limit, _)| Instant::now() + *limit);
+        while depth > 0 {
+            self.emit(ProgressEvent::FrontierExpanded {
+                frontier: ts.len(),
+                seen: ns.len(),
+            });
+            let batch: Vec<URL> = ts.iter().cloned().collect();
+            let arts = match self.get_list_until(&batch, deadline).await? {
+                Some(arts) => arts,
+                None => return Err(self.checkpoint(urls, depth, ts, ns)?),
+            };
+            ns.extend(ts);
+            let start = Instant::now();
+            let mut new_ts = HashSet::new();
+            for a in arts.iter().filter(|a| self.expands(a, urls)) {
+                for u in a.references.iter().cloned() {
+                    if ns.insert(u.clone()) {
+                        // We only need to fetch this value if we've not seen it before.very
+                        new_ts.insert(u);
+                    }
+                }
+            }
+            self.record_since(timing::PHASE_SEARCH, start);
+            ts = new_ts;
+            depth -= 1;
+        }
+        // Without any expansions, the frontier is all there is.
+        let batch: Vec<URL> = ns.union(&ts).cloned().collect();
+        match self.get_list_until(&batch, deadline).await? {
+            Some(arts) => Ok(self.collapse_redirects(arts)),
+            None => Err(self.checkpoint(urls, 0, ts, ns)?),
+        }
+    }
+
+    /// Does the same as Collector::get_list, but gives up once the deadline has passed and
+    /// returns `None` then. The articles received until then are cached.
+    async fn get_list_until(
+        &mut self,
+        urls: &[URL],
+        deadline: Option<Instant>,
+    ) -> Result<Option<Vec<Article>>, Box<dyn Error>> {
+        let deadline = match deadline {
+            Some(d) => d,
+            None => return self.get_list(urls).await.map(Some),
+        };
+        let token = CancellationToken::new();
+        let list = self.get_list_cancellable(urls, &token);
+        let timer = tokio::time::delay_until(deadline.into());
+        futures::pin_mut!(list);
+        let list = match future::select(list, timer).await {
+            Either::Left((res, _)) => return res.map(Some),
+            Either::Right((_, list)) => list,
+        };
+        token.cancel();
+        match list.await {
+            Ok(arts) => Ok(Some(arts)),
+            Err(e) => match e.downcast_ref::<CollectionErr>() {
+                Some(CollectionErr::Cancelled) => Ok(None),
+                _ => Err(e),
+            },
+        }
+    }
+
+    /// Writes a checkpoint of a neighbourhood crawl to the path given with the time limit
+    /// and returns the error that tells where to find it.
+    fn checkpoint(
+        &self,
+        urls: &[URL],
+        depth: u32,
+        ts: HashSet<URL>,
+        ns: HashSet<URL>,
+    ) -> Result<Box<dyn Error>, Box<dyn Error>> {
+        let path = match &self.time_limit {
+            Some((_, path)) => path.clone(),
+            None => return Ok(Box::new(CollectionErr::Cancelled)),
+        };
+        let articles = ts
+            .iter()
+            .chain(ns.iter())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter_map(|u| self.cache.get(u).cloned())
+            .collect();
+        Checkpoint {
+            seeds: urls.to_vec(),
+            depth,
+            frontier: ts.into_iter().collect(),
+            seen: ns.into_iter().collect(),
+            articles,
+        }
+        .save(&path)?;
+        Ok(Box::new(CollectionErr::TimeLimitExceeded(path)))
+    }
+
+    /// Whether the references of the given article are followed when searching from the
+    /// given starting points. See CollectorBuilder::min_out_degree.
+    fn expands(&self, a: &Article, start: &[URL]) -> bool {
+        a.references.len() >= self.min_out_degree || start.contains(&a.url)
+    }
+
+    /// Gets the neighbourhood of the given degree and reduces it to its breadth-first
+    /// spanning tree rooted at the given URL. See `Graph::bfs_tree`.
+    pub async fn get_bfs_tree(&mut self, url: &URL, depth: u32) -> Result<BfsTree, Box<dyn Error>> {
+        let arts = self.get_neighbourhood(url, depth).await?;
+        Graph::new(&arts)
+            .bfs_tree(url)
+            .ok_or_else(|| Box::new(CollectionErr::PathFindingError) as Box<dyn Error>)
+    }
+
+    /// Labels each of the given articles with the seed it is closest to. This is meant to
+    /// be used on the result of Collector::get_union_neighbourhood for the same seeds.
+    /// See `Graph::nearest_seed`.
+    pub fn nearest_seed(seeds: &[URL], articles: &[Article]) -> HashMap<URL, URL> {
+        Graph::new(articles).nearest_seed(seeds)
+    }
+
+    /// Counts how many distinct articles every seed can reach in at most `depth` steps,
+    /// not counting the seed itself. Every seed is searched separately, but they share
+    /// the cache, so no article is requested twice.
+    pub async fn reachability_counts(
+        &mut self,
+        seeds: &[URL],
+        depth: u32,
+    ) -> Result<HashMap<URL, usize>, Box<dyn Error>> {
+        let mut counts = HashMap::new();
+        for s in seeds {
+            let mut seen = HashSet::new();
+            seen.insert(s.clone());
+            let mut ts = vec![s.clone()];
+            for _ in 0..depth {
+                let arts = self.get_list(&ts).await?;
+                ts = Vec::new();
+                for a in arts
+                    .iter()
+                    .filter(|a| self.expands(a, std::slice::from_ref(s)))
+                {
+                    for u in a.references.iter() {
+                        if seen.insert(u.clone()) {
+                            ts.push(u.clone());
+                        }
+                    }
+                }
+            }
+            counts.insert(s.clone(), seen.len() - 1);
+        }
+        Ok(counts)
+    }
+
+    /// Summarizes the given articles, e.g. the result of a crawl. See `CrawlReport`.
+    pub fn report(articles: &[Article]) -> CrawlReport {
+        CrawlReport::new(articles)
+    }
+
+    /// Finds the paths between all the ordered pairs of distinct URLs in the given list.
+    ///
+    /// Results from an earlier, possibly interrupted, run may be passed in as `prior`. A pair
+    /// counts as solved if there is a result with the same `from` and `to`; those pairs are
+    /// skipped. The returned list contains all the prior results followed by the new ones.
+    pub async fn all_pairs_paths(
+        &mut self,
+        urls: &[URL],
+        prior: &[PathResult],
+    ) -> Result<Vec<PathResult>, Box<dyn Error>> {
+        let mut res = prior.to_vec();
+        for x in urls.iter() {
+            for y in urls.iter() {
+                if *x == *y || res.iter().any(|r| r.is_for(x, y)) {
+                    continue;
+                }
+                let path = self.get_path_urls(x, y).await?;
+                self.emit(ProgressEvent::PathFound {
+                    from: x.clone(),
+                    to: y.clone(),
+                    length: path.len() - 1,
+                });
+                res.push(PathResult {
+                    from: x.clone(),
+                    to: y.clone(),
+                    path,
+                });
+            }
+        }
+        Ok(res)
+    }
+
+    /// Given two URLs to valid Wikipedia articles this allows to find a chain of articles that
+    /// connects the two inputs by references.
+    ///
+    /// The path starts with the article for og and ends with the one for tg, so a direct
+    /// link gives a path of two articles and a path from an article to itself only has one.
+    pub async fn get_path(&mut self, og: &URL, tg: &URL) -> Result<Vec<Article>, Box<dyn Error>> {
+        let mut path = Vec::new();
+        for u in self.get_path_urls(og, tg).await? {
+            path.push(self.get(&u).await?);
+        }
+        Ok(path)
+    }
+
+    /// Finds a path from og to tg that passes through the given waypoint. This is a shortest
+    /// path from og to the waypoint followed by a shortest path from the waypoint to tg, so
+    /// the waypoint is only part of the path once.
+    ///
+    /// If either part does not exist, CollectionErr::PathFindingError is returned.
+    pub async fn get_path_via(
+        &mut self,
+        og: &URL,
+        waypoint: &URL,
+        tg: &URL,
+    ) -> Result<Vec<Article>, Box<dyn Error>> {
+        let mut urls = self.get_path_urls(og, waypoint).await?;
+        let rest = self.get_path_urls(waypoint, tg).await?;
+        let skip = if rest.first() == Some(waypoint) { 1 } else { 0 };
+        urls.extend(rest.into_iter().skip(skip));
+        let mut path = Vec::new();
+        for u in urls {
+            path.push(self.get(&u).await?);
+        }
+        Ok(path)
+    }
+
+    /// Does the same as Collector::get_path, but searches from both ends and stops as soon
+    /// as the two searches meet.
+    ///
+    /// The forward search from og fetches articles just like Collector::get_path. Wikipedia
+    /// can't be asked which articles link to a given one, though, so the backward search
+    /// from tg only follows the references of articles that are known already, i.e. that
+    /// are in the cache. Every article that links to tg or to another article of the
+    /// backward search joins it, and the forward search stops at the first article that is
+    /// part of the backward search.
+    ///
+    /// With a cold cache this fetches the same articles as Collector::get_path. Once the
+    /// cache knows articles within `k` steps of tg, e.g. from earlier searches or a cache
+    /// file, the forward search can stop `k` levels early. Since a level has about `b^d`
+    /// articles for a mean out-degree of `b`, that cuts the number of fetched pages by a
+    /// factor of about `b^k`, e.g. 100 for `b = 10` and `k = 2`.
+    ///
+    /// The maximum depth (see CollectorBuilder::max_depth) applies to the whole path, not
+    /// just to the forward search.
+    ///
+    /// Just like for Collector::get_path, the path starts with og and ends with tg. It is
+    /// a shortest path among the known references, but a shorter one may go through
+    /// articles the backward search did not know about.
+    pub async fn get_path_bidirectional(
+        &mut self,
+        og: &URL,
+        tg: &URL,
+    ) -> Result<Vec<Article>, Box<dyn Error>> {
+        if og.domain() != tg.domain() {
+            return Err(Box::new(CollectionErr::MixedDomains(
+                og.clone(),
+                tg.clone(),
+            )));
+        }
+        self.check_seeds(&[og.clone(), tg.clone()]).await?;
+        let mut parents: HashMap<URL, URL> = HashMap::new(); // Forward search tree
+        let mut ns: HashSet<URL> = HashSet::new(); // Encountered URLs
+        ns.insert(og.clone());
+        let mut ts = vec![og.clone()]; // "Unhandled URLs"
+        let mut depth = 0;
+        // The cache is only scanned once, the articles fetched later are added as they come.
+        let mut backward = BackwardSearch::new(tg);
+        for (u, a) in self.cache.iter() {
+            backward.add(u, &a.references);
+        }
+        let meeting = loop {
+            let next = &backward.next;
+            // Among the frontier, the article with the shortest remaining path wins. The
+            // whole path has to stay within the maximum depth, though.
+            let meeting = ts
+                .iter()
+                .filter_map(|u| next.get(u).map(|(d, _)| (*d, u)))
+                .filter(|(d, _)| self.max_depth.is_none_or(|m| depth + d <= m))
+                .min();
+            if let Some((_, u)) = meeting {
+                let mut path = Vec::new();
+                let mut v = u;
+                while let Some((_, Some(n))) = next.get(v) {
+                    path.push(n.clone());
+                    v = n;
+                }
+                break (u.clone(), path);
+            }
+            if ts.is_empty() || self.max_depth.is_some_and(|d| depth >= d) {
+                return Err(Box::new(CollectionErr::PathFindingError));
+            }
+            depth += 1;
+            let arts = self.get_list(&ts).await?;
+            let start = Instant::now();
+            let received: HashMap<&URL, &Article> = arts.iter().map(|a| (&a.url, a)).collect();
+            for u in ts.iter() {
+                // Articles that were requested through a redirect are known under both URLs.
+                let a = received.get(u).or_else(|| {
+                    self.redirects
+                        .get(u)
+                        .and_then(|target| received.get(target))
+                });
+                if let Some(a) = a {
+                    backward.add(u, &a.references);
+                }
+            }
+            let mut new_ts = Vec::new();
+            for a in arts
+                .iter()
+                .filter(|a| self.expands(a, std::slice::from_ref(og)))
+            {
+                for u in a.references_sorted() {
+                    if ns.insert(u.clone()) {
+                        parents.insert(u.clone(), a.url.clone());
+                        new_ts.push(u);
+                    }
+                }
+            }
+            if let Some(l) = self.frontier_limit {
+                if ns.len() > l {
+                    return Err(Box::new(CollectionErr::FrontierTooLarge(ns.len())));
+                }
+            }
+            self.record_since(timing::PHASE_SEARCH, start);
+            new_ts.sort();
+            ts = new_ts;
+        };
+        let (u, rest) = meeting;
+        let mut urls = vec![u.clone()];
+        let mut v = &u;
+        while let Some(p) = parents.get(v) {
+            urls.push(p.clone());
+            v = p;
+        }
+        urls.reverse();
+        urls.extend(rest);
+        let mut path = Vec::new();
+        for u in urls {
+            path.push(self.get(&u).await?);
+        }
+        Ok(path)
+    }
+
+    /// Does the same as Collector::get_path, but only returns the URLs of the articles along
+    /// the path. This is cheaper as none of the articles have to be copied.
+    pub async fn get_path_urls(&mut self, og: &URL, tg: &URL) -> Result<Vec<URL>, Box<dyn Error>> {
+        let (ns, _) = self.search_path(og, tg).await?;
+        self.find_path(og, tg, ns.into_iter().collect()).await
+    }
+
+    /// Finds up to `limit` different shortest paths from og to tg. Each of them starts with
+    /// og and ends with tg, just like the path of Collector::get_path, which is the first one.
+    ///
+    /// The number of shortest paths can grow exponentially with their length, which is
+    /// why it has to be limited.
+    pub async fn get_all_shortest_paths(
+        &mut self,
+        og: &URL,
+        tg: &URL,
+        limit: usize,
+    ) -> Result<Vec<Vec<Article>>, Box<dyn Error>> {
+        let (ns, _) = self.search_path(og, tg).await?;
+        let mut paths = Vec::new();
+        for urls in self
+            .find_paths(og, tg, ns.into_iter().collect(), limit)
+            .await?
+        {
+            let mut path = Vec::new();
+            for u in urls {
+                path.push(self.get(&u).await?);
+            }
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    /// Finds the subgraph that consists of all the articles on any shortest path from og to
+    /// tg, see Graph::shortest_path_subgraph. This is a lot smaller than everything that has
+    /// to be searched to find the path, so it is a lot easier to look at.
+    pub async fn get_path_subgraph(&mut self, og: &URL, tg: &URL) -> Result<Graph, Box<dyn Error>> {
+        let (_, mut arts) = self.search_path(og, tg).await?;
+        // The articles of the last step of the search are not requested and the path ends
+        // at the target anyway, so its references don't matter.
+        arts.retain(|a| a.url != *tg);
+        arts.push(Article::new(tg.clone()));
+        Ok(Graph::new(&arts).shortest_path_subgraph(og, tg))
+    }
+
+    /// Searches breadth-first from og until tg is encountered and returns all the URLs that
+    /// were encountered on the way, together with the articles that were expanded.
+    async fn search_path(
+        &mut self,
+        og: &URL,
+        tg: &URL,
+    ) -> Result<(HashSet<URL>, Vec<Article>), Box<dyn Error>> {
+        if og.domain() != tg.domain() {
+            // Articles only link to articles on their own domain.
+            return Err(Box::new(CollectionErr::MixedDomains(
+                og.clone(),
+                tg.clone(),
+            )));
+        }
+        self.check_seeds(&[og.clone(), tg.clone()]).await?;
+        let mut ts = HashSet::new(); // "Unhandled URLs"
+        let mut ns = HashSet::new(); // Encountered URLs
+        ts.insert(og.clone());
+        // If og is tg, the search is over before it starts, but og is still on the path.
+        ns.insert(og.clone());
+        let mut expanded = Vec::new();
+        let mut depth = 0;
+        while !ts.contains(tg) {
+            if ts.is_empty() || self.max_depth.is_some_and(|d| depth >= d) {
+                // Everything reachable (or everything within the depth) has been searched.
+                return Err(Box::new(CollectionErr::PathFindingError));
+            }
+            depth += 1;
+            ns.extend(ts.iter().cloned());
+            let arts = self.get_list(&ts.into_iter().collect::<Vec<_>>()).await?;
+            let start = Instant::now();
+            let mut new_ts = HashSet::new();
+            for a in arts
+                .iter()
+                .filter(|a| self.expands(a, std::slice::from_ref(og)))
+            {
+                for u in a.references.iter().cloned() {
+                    if ns.insert(u.clone()) {
+                        new_ts.insert(u);
+                    }
+                }
+            }
+            if let Some(l) = self.frontier_limit {
+                if ns.len() > l {
+                    return Err(Box::new(CollectionErr::FrontierTooLarge(ns.len())));
+                }
+            }
+            self.record_since(timing::PHASE_SEARCH, start);
+            expanded.extend(arts);
+            ts = new_ts;
+        }
+        Ok((ns, expanded))
+    }
+
+    /// Given a neighbourhood (i.e. a set, or rather a Vector, of URLs that are guranteed to contain a path between og and tg)
+    /// the path is found.
+    ///
+    /// CollectionErr::PathFindingError is returned if either og or tg is not in the given
+    /// set, or if the set does not contain a path from og to tg.
+    async fn find_path(
+        &mut self,
+        og: &URL,
+        tg: &URL,
+        ns: Vec<URL>,
+    ) -> Result<Vec<URL>, Box<dyn Error>> {
+        let (ns, adj, og_idx, tg_idx) = self.path_graph(og, tg, ns).await?;
+        let bd = binary_dijkstra(&adj, og_idx, tg_idx).ok_or(CollectionErr::PathFindingError)?;
+        Ok(bd.into_iter().map(|i| ns[i].clone()).collect())
+    }
+
+    /// Does the same as Collector::find_path, but finds up to `limit` shortest paths.
+    async fn find_paths(
+        &mut self,
+        og: &URL,
+        tg: &URL,
+        ns: Vec<URL>,
+        limit: usize,
+    ) -> Result<Vec<Vec<URL>>, Box<dyn Error>> {
+        let (ns, adj, og_idx, tg_idx) = self.path_graph(og, tg, ns).await?;
+        let paths = shortest_paths(&adj, og_idx, tg_idx, limit.max(1));
+        Ok(paths
+            .into_iter()
+            .map(|p| p.into_iter().map(|i| ns[i].clone()).collect())
+            .collect())
+    }
+
+    /// Builds the graph that Collector::find_path searches, i.e. the neighbourhood, the
+    /// adjacency lists of the nodes up to the distance of tg and the indices of og and tg.
+    /// Fails with CollectionErr::PathFindingError if there is no path in the neighbourhood.
+    async fn path_graph(
+        &mut self,
+        og: &URL,
+        tg: &URL,
+        ns: Vec<URL>,
+    ) -> Result<(Vec<URL>, Vec<Vec<usize>>, usize, usize), Box<dyn Error>> {
+        let index: HashMap<URL, usize> = ns.iter().cloned().zip(0..).collect();
+        let l = ns.len();
+        let mut adj = vec![Vec::new(); l];
+        let mut dist = vec![usize::MAX; l];
+        let og_idx = *index.get(og).ok_or(CollectionErr::PathFindingError)?;
+        let tg_idx = *index.get(tg).ok_or(CollectionErr::PathFindingError)?;
+        let mut q = VecDeque::new();
+        q.push_back(og_idx);
+        dist[og_idx] = 0;
+        // Every node that is closer than tg is expanded, so all the shortest paths are known.
+        while q.front().is_some_and(|v| dist[*v] < dist[tg_idx]) {
+            let v = q.pop_front().unwrap();
+            // The article may be evicted right away if the cache is small, so the one that
+            // was received is used directly.
+            let received = if self.cache.contains_key(&ns[v]) {
+                None
+            } else {
+                Some(self.get(&ns[v]).await?)
+            };
+            let a = match &received {
+                Some(a) => a,
+                None => &self.cache[&ns[v]],
+            };
+            for r in a.references.iter() {
+                if let Some(&k) = index.get(r) {
+                    adj[v].push(k); // Create edge v -> k
+                    if dist[k] == usize::MAX {
+                        // If we've already seen this then we don't need to visit it again.
+                        q.push_back(k);
+                        dist[k] = dist[v] + 1;
+                    }
+                }
+            }
+        }
+        // The target could not be visited before exhausting the neighbourhood.
+        if dist[tg_idx] == usize::MAX {
+            return Err(Box::new(CollectionErr::PathFindingError));
+        }
+        // The references are not ordered, but the paths should not depend on that.
+        for ks in adj.iter_mut() {
+            ks.sort_unstable_by(|a, b| ns[*a].cmp(&ns[*b]));
+        }
+        Ok((ns, adj, og_idx, tg_idx))
+    }
+}
+
+/// The backward search of Collector::get_path_bidirectional. It knows the distance to
+/// tg of every article that is known to reach it, together with the next article on the
+/// way. The next article is the smallest one if there is a choice.
+///
+/// Articles are added one by one, and only the distances they change are updated.
+struct BackwardSearch {
+    /// The distance to tg and the next article on the way for all the articles that reach tg.
+    next: HashMap<URL, (usize, Option<URL>)>,
+    /// The articles that were added so far which reference the URL.
+    linkers: HashMap<URL, Vec<URL>>,
+    /// The articles that were added so far.
+    added: HashSet<URL>,
+}
+
+impl BackwardSearch {
+    fn new(tg: &URL) -> Self {
+        let mut next = HashMap::new();
+        next.insert(tg.clone(), (0, None));
+        BackwardSearch {
+            next,
+            linkers: HashMap::new(),
+            added: HashSet::new(),
+        }
+    }
+
+    /// Adds the article at url with the given references, unless it was added already.
+    fn add(&mut self, url: &URL, references: &HashSet<URL>) {
+        if !self.added.insert(url.clone()) {
+            return;
+        }
+        for r in references {
+            self.linkers.entry(r.clone()).or_default().push(url.clone());
+        }
+        let best = references
+            .iter()
+            .filter_map(|r| self.next.get(r).map(|(d, _)| (d + 1, Some(r.clone()))))
+            .min();
+        if let Some(best) = best {
+            if self.improve(url, best) {
+                self.propagate(url.clone());
+            }
+        }
+    }
+
+    /// Replaces the distance and the next article of url if the new ones are better.
+    fn improve(&mut self, url: &URL, candidate: (usize, Option<URL>)) -> bool {
+        if self.next.get(url).is_some_and(|old| *old <= candidate) {
+            return false;
+        }
+        self.next.insert(url.clone(), candidate);
+        true
+    }
+
+    /// Updates the articles that link to url, and the ones that link to those, and so on,
+    /// after url got closer to tg.
+    fn propagate(&mut self, url: URL) {
+        let mut ts = VecDeque::new();
+        ts.push_back(url);
+        while let Some(u) = ts.pop_front() {
+            let d = self.next[&u].0;
+            let linkers = self.linkers.get(&u).cloned().unwrap_or_default();
+            for l in linkers {
+                if self.improve(&l, (d + 1, Some(u.clone()))) {
+                    ts.push_back(l);
+                }
+            }
+        }
+    }
+}
+
+/// The articles of one level of a neighbourhood, see Collector::level_stream.
+type LevelStream<'a> = LocalBoxStream<'a, (URL, Result<Article, Box<dyn Error>>)>;
+
+/// The state of Collector::get_neighbourhood_stream between two articles.
+struct NeighbourhoodStream<'a> {
+    collector: &'a Collector,
+    seeds: Vec<URL>,
+    /// The level that is being received, or None once everything was received.
+    level: Option<LevelStream<'a>>,
+    /// All the URLs that were requested or are going to be.
+    seen: HashSet<URL>,
+    /// The URLs of the next level that were found so far.
+    next: HashSet<URL>,
+    /// The number of levels that are still going to be requested after this one.
+    remaining: u32,
+    failed: bool,
+}
+
+/// A simplified (but naive) version of Dijkstra's algorithm to find a path in a directed graph
+/// without edge weights. The graph is given by adjacency lists, i.e. `adj[v]` contains the
+/// indices of all the nodes that v has edges to. Wikipedia is very sparse, so this needs a
+/// lot less memory than an adjacency matrix.
+///
+/// The main advantage of these constraints is that the first distance that is determined for any
+/// single node is guranteed to be the shortest distance as all the edges have the same length and
+/// any other node is at least as many steps away from the origin.
+///
+/// The returned path contains the indices of both og and tg, in the order they are visited.
+/// If tg can't be reached from og, there is no path.
+///
+/// # Panics
+///
+/// If an adjacency list contains an index that is not a node, i.e. not less than `adj.len()`.
+fn binary_dijkstra(adj: &[Vec<usize>], og: usize, tg: usize) -> Option<Vec<usize>> {
+    shortest_paths(adj, og, tg, 1).pop()
+}
+
+/// Does the same as binary_dijkstra, but returns up to `limit` different shortest paths.
+///
+/// Every node keeps all its predecessors at the shortest distance, in the order they were
+/// found, and the paths are enumerated by backtracking from tg. So the first path is the
+/// one binary_dijkstra returns.
+fn shortest_paths(adj: &[Vec<usize>], og: usize, tg: usize, limit: usize) -> Vec<Vec<usize>> {
+    let l = adj.len();
+    if og >= l || tg >= l {
+        return Vec::new();
+    }
+    let mut from = vec![Vec::new(); l]; // The neighbours the shortest paths to a node come from.
+    let mut dist = vec![-1; l]; // Distance of a node from og, or -1 (i.e. infinity) if it hasn't been reached yet.
+    let mut q = VecDeque::new(); // Queue of vertices to handle
+    dist[og] = 0;
+    q.push_back(og);
+    // All the nodes before tg have to be handled to know all of its predecessors.
+    while let Some(v) = q.pop_front() {
+        if dist[tg] >= 0 && dist[v] >= dist[tg] {
+            break;
+        }
+        for &n in adj[v].iter() {
+            if dist[n] < 0 {
+                dist[n] = dist[v] + 1;
+                q.push_back(n);
+            }
+            if dist[n] == dist[v] + 1 {
+                from[n].push(v);
+            }
+        }
+    }
+    if dist[tg] < 0 {
+        return Vec::new();
+    }
+    let mut paths = Vec::new();
+    let mut path = vec![tg];
+    collect_paths(&from, og, &mut path, &mut paths, limit);
+    paths
+}
+
+/// Extends a path that is built backwards from its end along all the given predecessors
+/// until it reaches og, and adds every complete path to `paths` until there are `limit`.
+fn collect_paths(
+    from: &[Vec<usize>],
+    og: usize,
+    path: &mut Vec<usize>,
+    paths: &mut Vec<Vec<usize>>,
+    limit: usize,
+) {
+    let v = *path.last().unwrap();
+    if v == og {
+        paths.push(path.iter().rev().copied().collect());
+        return;
+    }
+    for &p in from[v].iter() {
+        if paths.len() >= limit {
+            return;
+        }
+        path.push(p);
+        collect_paths(from, og, path, paths, limit);
+        path.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::*;
+
+    /// A trimmed snapshot of `/wiki/Tree` with the chrome around the content.
+    const TREE_SNAPSHOT: &str = r##"<!DOCTYPE html>
+<html class="client-nojs" lang="en" dir="ltr">
+<head>
+<meta charset="UTF-8">
+<title>Tree - Wikipedia</title>
+<script>RLCONF={"wgPageName":"Tree","wgTitle":"Tree"};document.write('<a href="/wiki/Scripted">');</script>
+</head>
+<body class="skin-vector mediawiki ltr">
+<div id="mw-navigation">
+<a class="mw-wiki-logo" href="/wiki/Main_Page" title="Visit the main page"></a>
+<ul><li id="n-randompage"><a href="/wiki/Special:Random" title="Visit a randomly selected article">Random article</a></li>
+<li id="n-portal"><a href="/wiki/Wikipedia:Community_portal">Community portal</a></li></ul>
+</div>
+<div id="siteNotice"><a href="/wiki/Wiki_Loves_Earth">Photograph nature</a> and win!</div>
+<div id="content" class="mw-body">
+<h1 id="firstHeading" class="firstHeading">Tree</h1>
+<div id="mw-content-text" class="mw-body-content"><div class="mw-parser-output">
+<figure><a href="/wiki/File:Tree.jpg" class="mw-file-description"><img src="tree.jpg"></a></figure>
+<p>In <a href="/wiki/Botany" title="Botany">botany</a>, a <b>tree</b> is a <a class="mw-redirect" href="/wiki/Perennial_plant" title="Perennial plant">perennial plant</a>
+with an elongated <a
+href="/wiki/Plant_stem" title="Plant stem">stem</a>, or trunk, usually supporting branches and leaves.<sup id="cite_ref-1" class="reference"><a href="#cite_note-1">[1]</a></sup></p>
+<p>Most trees produce <a href="/wiki/Wood" title="Wood">wood</a>.</p>
+</div></div>
+<div id="catlinks" class="catlinks"><ul>
+<li><a href="/wiki/Category:Trees" title="Category:Trees">Trees</a></li>
+<li><a href="/wiki/Category:Plant_life-forms" title="Category:Plant life-forms">Plant life-forms</a></li>
+</ul></div>
+</div>
+<div id="footer"><a href="https://foundation.wikimedia.org/wiki/Privacy_policy">Privacy policy</a></div>
+</body>
+</html>
+"##;
+
+    #[test]
+    fn parse_only_uses_content_links() -> Result<(), Box<dyn Error>> {
+        let names = |a: &Article| {
+            let mut ns: Vec<_> = a.references.iter().map(|r| r.get_name()).collect();
+            ns.sort();
+            ns
+        };
+        let a = Article::parse(url("Tree"), String::from(TREE_SNAPSHOT))?;
+        assert_eq!(
+            names(&a),
+            ["Botany", "Perennial plant", "Plant stem", "Wood"]
+        );
+        let mut categories: Vec<_> = a.categories.iter().cloned().collect();
+        categories.sort();
+        assert_eq!(categories, ["Plant life-forms", "Trees"]);
+        // Scanning finds the links in the chrome, but not the ones that don't start like
+        // the ones above.
+        let opts = ParseOptions {
+            backend: ParseBackend::Scan,
+            ..ParseOptions::default()
+        };
+        let a = Article::parse_with(url("Tree"), String::from(TREE_SNAPSHOT), &opts)?;
+        assert_eq!(
+            names(&a),
+            ["Botany", "Scripted", "Wiki Loves Earth", "Wood"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fixtures_are_parsed_into_references() -> Result<(), Box<dyn Error>> {
+        let fixture = "<p><a href=\"/wiki/Oak\">oak</a> and <a href=\"/wiki/Oak\">oak</a> again</p>\n\
+             <p><a href=\"/wiki/Beech#Description\">a section</a>, <a href=\"#cite_note-1\">[1]</a>,\
+             <a href=\"https://example.com/wiki/Elm\">elsewhere</a>, <a href=\"/w/index.php?title=Ash\">raw</a></p>\n\
+             <p><a href=\"/wiki/File:Oak.jpg\">file</a> <a href=\"/wiki/Special:Random\">random</a>\
+             <a href=\"/wiki/Talk:Oak\">talk</a> <a href=\"/wiki/Birch\">birch</a></p>\n";
+        for backend in [ParseBackend::default(), ParseBackend::Scan].iter() {
+            let opts = ParseOptions {
+                backend: backend.clone(),
+                ..ParseOptions::default()
+            };
+            assert_eq!(
+                from_html_fixture(fixture, &opts)?,
+                ["Beech", "Birch", "Oak"]
+            );
+        }
+        assert!(from_html_fixture("", &ParseOptions::default())?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn unterminated_links_are_rejected() {
+        // The quote that closes the link would be on the next line.
+        let fixture = "<p><a href=\"/wiki/Oak\">oak</a> <a href=\"/wiki/Beech\n\">beech</a></p>\n";
+        let opts = ParseOptions {
+            backend: ParseBackend::Scan,
+            ..ParseOptions::default()
+        };
+        let e = from_html_fixture(fixture, &opts).unwrap_err();
+        assert!(matches!(
+            e.downcast_ref::<ArticleErr>(),
+            Some(ArticleErr::UnexpectedEOL)
+        ));
+    }
+
+    #[test]
+    fn scanning_handles_long_lines() -> Result<(), Box<dyn Error>> {
+        let line =
+            "<p>Größe → <a href=\"/wiki/Baum\">Baum</a>😀<a href=\"/wiki/Wald\">".repeat(1000);
+        let links = Article::scan_links(&format!("{}\n<a href=\"/wiki/Holz\">\n", line))?;
+        assert_eq!(links.len(), 2001);
+        assert_eq!(links[..2], ["/wiki/Baum", "/wiki/Wald"]);
+        assert_eq!(links[2000], "/wiki/Holz");
+        // A link must not continue on the next line.
+        assert!(Article::scan_links("<a href=\"/wiki/Baum\n\">").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn reference_section_can_be_ignored() -> Result<(), Box<dyn Error>> {
+        let site = content(
+            "<p>A <a href=\"/wiki/Tree\">tree</a><sup><a href=\"#cite_note-1\">[1]</a></sup>.</p>\n\
+             <div class=\"mw-heading\"><h2 id=\"See_also\">See also</h2></div>\n\
+             <ul><li><a href=\"/wiki/Forest\">Forest</a></li></ul>\n\
+             <div class=\"notes\"><ol class=\"references\"><li>\
+             <a href=\"/wiki/Oxford_University_Press\">OUP</a></li></ol></div>\n\
+             <h2><span class=\"mw-headline\" id=\"References\">References</span>\
+             <span class=\"mw-editsection\">[edit]</span></h2>\n\
+             <ul><li><a href=\"/wiki/Encyclopaedia_Britannica\">Britannica</a></li></ul>\n\
+             <h2>External links</h2>\n\
+             <ul><li><a href=\"/wiki/Arbor_Day_Foundation\">Arbor Day</a></li></ul>\n",
+        );
+        let names = |opts: &ParseOptions| -> Result<Vec<String>, Box<dyn Error>> {
+            let a = Article::parse_with(url("Oak"), site.clone(), opts)?;
+            let mut ns: Vec<_> = a.references.iter().map(|r| r.get_name()).collect();
+            ns.sort();
+            Ok(ns)
+        };
+        assert_eq!(names(&ParseOptions::default())?.len(), 5);
+        let opts = ParseOptions {
+            ignore_reference_section: true,
+            ..ParseOptions::default()
+        };
+        assert_eq!(names(&opts)?, ["Forest", "Tree"]);
+        Ok(())
+    }
+
+    const MIXED_LINKS: &str = "<p><a href=\"/wiki/Tree\" title=\"Tree\">tree</a> and \
+        <a href=\"/wiki/Help:Contents\">help</a></p>\n\
+        <a href=\"/wiki/Mercury_(disambiguation)\">mercury</a> \
+        <a href=\"/wiki/Plant#Roots\">roots</a> <a href=\"/wiki/Help:Contents\">help</a>";
+
+    #[test]
+    fn parse_records_dropped_links() -> Result<(), Box<dyn Error>> {
+        let url = URL::new("/wiki/Forest")?;
+        let opts = ParseOptions {
+            record_dropped: true,
+            ..ParseOptions::default()
+        };
+        let a = Article::parse_with(url, content(MIXED_LINKS), &opts)?;
+        let mut refs: Vec<_> = a.references.iter().map(|u| u.get_name()).collect();
+        refs.sort();
+        assert_eq!(refs, vec!["Plant", "Tree"]);
+        let dropped = a.dropped.unwrap();
+        let links: Vec<_> = dropped.iter().map(|d| d.link.as_str()).collect();
+        assert_eq!(
+            links,
+            vec!["/wiki/Help:Contents", "/wiki/Mercury_(disambiguation)"]
+        );
+        assert!(dropped[1].reason.contains("suffix"));
+        Ok(())
+    }
+
+    #[test]
+    fn selector_restricts_links() -> Result<(), Box<dyn Error>> {
+        let site = "<div class=\"mw-parser-output\">\
+            <p>A <a href=\"/wiki/Tree\">tree</a> is a <a href=\"/wiki/Plant\">plant</a>.</p>\
+            <ul><li><a href=\"/wiki/Forest\">forest</a></li></ul>\
+            <p><a href=\"/wiki/Help:Contents\">help</a> <a href=\"https://example.com\">x</a></p>\
+            </div><p><a href=\"/wiki/Outside\">outside</a></p>";
+        let opts = ParseOptions {
+            backend: ParseBackend::html(".mw-parser-output > p a")?,
+            record_dropped: true,
+            ..ParseOptions::default()
+        };
+        let a = Article::parse_with(url("Wood"), String::from(site), &opts)?;
+        let mut names: Vec<_> = a.references.iter().map(|r| r.get_name()).collect();
+        names.sort();
+        assert_eq!(names, ["Plant", "Tree"]);
+        assert_eq!(a.dropped.unwrap().len(), 1);
+        // Selecting a container uses all the links in it.
+        let opts = ParseOptions {
+            backend: ParseBackend::html(".mw-parser-output")?,
+            ..ParseOptions::default()
+        };
+        let a = Article::parse_with(url("Wood"), String::from(site), &opts)?;
+        assert_eq!(a.references.len(), 3);
+        assert!(matches!(
+            ParseBackend::html("p >> a"),
+            Err(ArticleErr::InvalidSelector(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn coverage_counts_anchors() -> Result<(), Box<dyn Error>> {
+        let site = "<div id=\"mw-content-text\">\
+            <p><a href=\"/wiki/Tree\">tree</a> <a href=\"/wiki/Tree\">again</a>\
+            <a href=\"/wiki/Plant\">plant</a> <a href=\"/wiki/Help:Contents\">help</a>\
+            <a href=\"https://example.com\">x</a> <a name=\"anchor\">y</a></p>\
+            </div><div id=\"footer\"><a href=\"/wiki/Main_Page\">main</a></div>";
+        let opts = ParseOptions {
+            backend: ParseBackend::html("#mw-content-text")?,
+            ..ParseOptions::default()
+        };
+        let (a, coverage) = Article::parse_with_coverage(url("Forest"), String::from(site), &opts)?;
+        assert_eq!(
+            a,
+            Article::parse_with(url("Forest"), String::from(site), &opts)?
+        );
+        assert_eq!(a.references.len(), 2);
+        assert_eq!(
+            coverage,
+            ParseCoverage {
+                anchors: 7,
+                wiki_anchors: 5,
+                accepted: 3,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_does_not_record_dropped_links_by_default() -> Result<(), Box<dyn Error>> {
+        let a = Article::parse(URL::new("/wiki/Forest")?, content(MIXED_LINKS))?;
+        assert_eq!(a.references.len(), 2);
+        assert!(a.dropped.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn union_neighbourhood_is_union_of_neighbourhoods() -> Result<(), Box<dyn Error>> {
+        let edges: &[(&str, &[&str])] = &[
+            ("A", &["B", "C"]),
+            ("B", &["D"]),
+            ("C", &["A"]),
+            ("D", &["E"]),
+            ("E", &[]),
+            ("F", &["G", "C"]),
+            ("G", &["H"]),
+            ("H", &[]),
+        ];
+        let names =
+            |arts: Vec<Article>| -> HashSet<URL> { arts.into_iter().map(|a| a.url).collect() };
+        let mut c = Collector::builder()
+            .fetcher(MockFetcher::graph(edges))
+            .build();
+        let seeds = vec![url("A"), url("F")];
+        for depth in 0..3 {
+            let mut separate = names(block_on(c.get_neighbourhood(&seeds[0], depth))?);
+            separate.extend(names(block_on(c.get_neighbourhood(&seeds[1], depth))?));
+            let union = block_on(c.get_union_neighbourhood(&seeds, depth))?;
+            assert_eq!(union.len(), separate.len());
+            assert_eq!(names(union), separate);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn neighbourhood_grows_by_one_level_per_depth() -> Result<(), Box<dyn Error>> {
+        let edges: &[(&str, &[&str])] = &[
+            ("A", &["B", "C"]),
+            ("B", &["D"]),
+            ("C", &["D", "A"]),
+            ("D", &["E", "F", "G"]),
+            ("E", &[]),
+            ("F", &[]),
+            ("G", &[]),
+        ];
+        let mut c = Collector::builder()
+            .fetcher(MockFetcher::graph(edges))
+            .build();
+        let sizes = (0..5)
+            .map(|d| Ok(block_on(c.get_neighbourhood(&url("A"), d))?.len()))
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+        assert_eq!(sizes, [1, 3, 4, 7, 7]);
+        let names: HashSet<_> = block_on(c.get_neighbourhood(&url("A"), 1))?
+            .into_iter()
+            .map(|a| a.url)
+            .collect();
+        assert_eq!(
+            names,
+            [url("A"), url("B"), url("C")].iter().cloned().collect()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn neighbourhood_can_be_streamed() -> Result<(), Box<dyn Error>> {
+        let edges: &[(&str, &[&str])] = &[
+            ("A", &["B", "C"]),
+            ("B", &["D", "A"]),
+            ("C", &["D"]),
+            ("D", &["E"]),
+            ("E", &["F"]),
+            ("F", &[]),
+        ];
+        for depth in 0..4 {
+            let fetcher = Arc::new(MockFetcher::graph(edges));
+            let c = Collector::builder().fetcher(fetcher.clone()).build();
+            let streamed = block_on(
+                c.get_neighbourhood_stream(&url("A"), depth)
+                    .collect::<Vec<_>>(),
+            );
+            let streamed = streamed.into_iter().collect::<Result<Vec<_>, _>>()?;
+            // Every article is yielded and requested exactly once, and none is cached.
+            assert_eq!(fetcher.requests(), streamed.len());
+            assert_eq!(c.stats().cache_size, 0);
+            let mut c = Collector::builder()
+                .fetcher(MockFetcher::graph(edges))
+                .build();
+            let collected = block_on(c.get_neighbourhood(&url("A"), depth))?;
+            let names: HashSet<_> = streamed.iter().map(|a| a.url.clone()).collect();
+            assert_eq!(names.len(), streamed.len());
+            assert_eq!(names, collected.into_iter().map(|a| a.url).collect());
+        }
+        // The stream ends after the first error.
+        let c = Collector::builder()
+            .fetcher(MockFetcher::graph(&[("A", &["B"])]))
+            .build();
+        let res = block_on(c.get_neighbourhood_stream(&url("A"), 2).collect::<Vec<_>>());
+        assert_eq!(res.len(), 2);
+        assert!(res[0].is_ok() && res[1].is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn low_degree_articles_are_not_expanded() -> Result<(), Box<dyn Error>> {
+        let edges: &[(&str, &[&str])] = &[
+            ("A", &["B"]),
+            ("B", &["C"]),
+            ("C", &["D", "E"]),
+            ("D", &["F"]),
+            ("E", &[]),
+            ("F", &[]),
+        ];
+        let names = |arts: Vec<Article>| -> Vec<String> {
+            let mut ns: Vec<_> = arts.into_iter().map(|a| a.url.get_name()).collect();
+            ns.sort();
+            ns
+        };
+        let mut c = Collector::builder()
+            .fetcher(MockFetcher::graph(edges))
+            .build();
+        assert_eq!(
+            names(block_on(c.get_neighbourhood(&url("C"), 2))?),
+            ["C", "D", "E", "F"]
+        );
+        let mut c = Collector::builder()
+            .fetcher(MockFetcher::graph(edges))
+            .min_out_degree(2)
+            .build();
+        // D has a single reference, so F is never reached.
+        assert_eq!(
+            names(block_on(c.get_neighbourhood(&url("C"), 2))?),
+            ["C", "D", "E"]
+        );
+        // The start is expanded even though it has only one reference, but B isn't.
+        assert_eq!(
+            names(block_on(c.get_neighbourhood(&url("A"), 2))?),
+            ["A", "B"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn path_urls_match_full_path() -> Result<(), Box<dyn Error>> {
+        let fetcher = Arc::new(MockFetcher::graph(&[
+            ("A", &["B", "C"]),
+            ("B", &["C"]),
+            ("C", &["A"]),
+        ]));
+        let mut c = Collector::builder().fetcher(fetcher.clone()).build();
+        for (x, y) in [("A", "B"), ("B", "C"), ("C", "A")] {
+            let urls = block_on(c.get_path_urls(&url(x), &url(y)))?;
+            let path = block_on(c.get_path(&url(x), &url(y)))?;
+            assert_eq!(urls.first(), Some(&url(x)));
+            assert_eq!(urls.last(), Some(&url(y)));
+            assert_eq!(urls, path.into_iter().map(|a| a.url).collect::<Vec<_>>());
+        }
+        assert_eq!(fetcher.requests(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn exhausted_retry_budget_aborts_crawl() {
+        let fetcher = Arc::new(
+            MockFetcher::graph(&[("A", &["B"]), ("B", &[]), ("C", &[])])
+                .with_status("A", 503)
+                .with_status("B", 503)
+                .with_status("C", 404),
+        );
+        let mut c = Collector::builder()
+            .fetcher(fetcher.clone())
+            .retries(5, Duration::from_millis(0))
+            .retry_budget(2)
+            .build();
+        // Not found is not worth retrying and doesn't use up the budget.
+        assert!(block_on(c.get(&url("C"))).is_err());
+        assert_eq!(fetcher.requests(), 1);
+        let e = block_on(c.get(&url("A"))).unwrap_err();
+        assert!(matches!(
+            e.downcast_ref(),
+            Some(CollectionErr::RetryBudgetExhausted(FetchErr::Status(503)))
+        ));
+        assert_eq!(fetcher.requests(), 4);
+        // Every further failure aborts immediately.
+        let e = block_on(c.get_neighbourhood(&url("B"), 1)).unwrap_err();
+        assert!(e.downcast_ref::<CollectionErr>().is_some());
+        assert_eq!(fetcher.requests(), 5);
+    }
+
+    #[test]
+    fn frontier_limit_stops_path_finding() -> Result<(), Box<dyn Error>> {
+        let edges: &[(&str, &[&str])] = &[
+            ("A", &["B", "C", "D"]),
+            ("B", &["E", "F"]),
+            ("C", &[]),
+            ("D", &[]),
+            ("E", &[]),
+            ("F", &[]),
+            ("Z", &["A"]),
+        ];
+        let mut c = Collector::builder()
+            .fetcher(MockFetcher::graph(edges))
+            .frontier_limit(4)
+            .build();
+        assert_eq!(block_on(c.get_path_urls(&url("A"), &url("D")))?.len(), 2);
+        let e = block_on(c.get_path_urls(&url("A"), &url("Z"))).unwrap_err();
+        assert!(matches!(
+            e.downcast_ref(),
+            Some(CollectionErr::FrontierTooLarge(6))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn max_depth_stops_path_finding() -> Result<(), Box<dyn Error>> {
+        let edges: &[(&str, &[&str])] =
+            &[("A", &["B"]), ("B", &["C"]), ("C", &["D"]), ("D", &["A"])];
+        let fetcher = Arc::new(MockFetcher::graph(edges));
+        let mut c = Collector::builder()
+            .fetcher(fetcher.clone())
+            .max_depth(2)
+            .build();
+        assert_eq!(block_on(c.get_path_urls(&url("A"), &url("C")))?.len(), 3);
+        let e = block_on(c.get_path_urls(&url("A"), &url("D"))).unwrap_err();
+        assert!(matches!(
+            e.downcast_ref(),
+            Some(CollectionErr::PathFindingError)
+        ));
+        let e = block_on(c.get_path_bidirectional(&url("B"), &url("A"))).unwrap_err();
+        assert!(matches!(
+            e.downcast_ref(),
+            Some(CollectionErr::PathFindingError)
+        ));
+        // Only the seeds and B were fetched.
+        assert_eq!(fetcher.requests(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_references_policies() -> Result<(), Box<dyn Error>> {
+        let edges: &[(&str, &[&str])] = &[("A", &["Stub", "B"]), ("B", &["A"]), ("Stub", &[])];
+        let collector = |policy| {
+            Collector::builder()
+                .fetcher(MockFetcher::graph(edges))
+                .empty_references(policy)
+                .build()
+        };
+        let mut c = collector(EmptyReferences::Keep);
+        assert_eq!(block_on(c.get_neighbourhood(&url("A"), 1))?.len(), 3);
+        let mut c = collector(EmptyReferences::Drop);
+        let arts = block_on(c.get_neighbourhood(&url("A"), 1))?;
+        assert_eq!(arts.len(), 2);
+        assert!(arts.iter().all(|a| a.url != url("Stub")));
+        assert_eq!(c.cached_articles().len(), 3);
+        let mut c = collector(EmptyReferences::Fail);
+        let e = block_on(c.get_neighbourhood(&url("A"), 1)).unwrap_err();
+        assert!(matches!(
+            e.downcast_ref(),
+            Some(CollectionErr::NoReferences(u)) if *u == url("Stub")
+        ));
+        assert!(block_on(c.get(&url("Stub"))).is_err());
+        assert_eq!(block_on(c.get(&url("B")))?.references.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn reachability_counts_compare_seeds() -> Result<(), Box<dyn Error>> {
+        let fetcher = Arc::new(MockFetcher::graph(&[
+            ("A", &["B", "C"]),
+            ("B", &["D", "E"]),
+            ("C", &["A"]),
+            ("D", &["F"]),
+            ("E", &[]),
+            ("F", &[]),
+            ("G", &["E"]),
+        ]));
+        let mut c = Collector::builder().fetcher(fetcher.clone()).build();
+        let seeds = vec![url("A"), url("G"), url("E")];
+        let counts = block_on(c.reachability_counts(&seeds, 2))?;
+        assert_eq!(counts[&url("A")], 4);
+        assert_eq!(counts[&url("G")], 1);
+        assert_eq!(counts[&url("E")], 0);
+        let counts = block_on(c.reachability_counts(&seeds, 3))?;
+        assert_eq!(counts[&url("A")], 5);
+        // A, B, C, D, E and G
+        assert_eq!(fetcher.requests(), 6);
+        Ok(())
+    }
+
+    #[test]
+    fn timings_are_folded_by_phase() -> Result<(), Box<dyn Error>> {
+        let fetcher = MockFetcher::graph(&[("A", &["B", "C"]), ("B", &["C"]), ("C", &[])])
+            .with_delay("B", Duration::from_millis(5));
+        let mut c = Collector::builder()
+            .fetcher(fetcher)
+            .record_timings(true)
+            .build();
+        block_on(c.get_neighbourhood(&url("A"), 2))?;
+        let t = c.timings().unwrap();
+        assert!(t.get(timing::PHASE_FETCH) >= Duration::from_millis(5));
+        let folded = t.folded();
+        let phases: Vec<_> = folded
+            .lines()
+            .map(|l| {
+                let (phase, us) = l.split_at(l.find(' ').unwrap());
+                assert!(us.trim().parse::<u128>().unwrap() > 0);
+                phase
+            })
+            .collect();
+        assert_eq!(
+            phases,
+            ["crawl;get;fetch", "crawl;get;parse", "crawl;search"]
+        );
+        assert!(Collector::new().timings().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn path_via_passes_through_waypoint() -> Result<(), Box<dyn Error>> {
+        let mut c = Collector::builder()
+            .fetcher(MockFetcher::graph(&[
+                ("A", &["W", "T"]),
+                ("W", &["T"]),
+                ("T", &[]),
+            ]))
+            .build();
+        let path = block_on(c.get_path_via(&url("A"), &url("W"), &url("T")))?;
+        let urls: Vec<_> = path.iter().map(|a| a.url.clone()).collect();
+        assert_eq!(urls.iter().filter(|u| **u == url("W")).count(), 1);
+        assert_eq!(urls.first(), Some(&url("A")));
+        assert_eq!(urls.last(), Some(&url("T")));
+        for p in path.windows(2) {
+            assert!(p[0].references.contains(&p[1].url));
+        }
+        let e = block_on(c.get_path_via(&url("A"), &url("T"), &url("W"))).unwrap_err();
+        assert!(matches!(
+            e.downcast_ref(),
+            Some(CollectionErr::PathFindingError)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn articles_stay_on_their_domain() -> Result<(), Box<dyn Error>> {
+        let de = DomainConfig::for_domain("https://de.wikipedia.org").unwrap();
+        let baum = URL::new_in("/wiki/Baum", de, CaseSensitivity::default())?;
+        let fetcher = Arc::new(MockFetcher::default().with_page(
+            "https://de.wikipedia.org/wiki/Baum",
+            &page(&["Größe", "Pflanze"]),
+        ));
+        let mut c = Collector::builder().fetcher(fetcher.clone()).build();
+        let a = block_on(c.get(&baum))?;
+        let mut refs: Vec<_> = a.references.iter().map(|r| r.to_string()).collect();
+        refs.sort();
+        assert_eq!(
+            refs,
+            vec![
+                "https://de.wikipedia.org/wiki/Größe",
+                "https://de.wikipedia.org/wiki/Pflanze"
+            ]
+        );
+        let e = block_on(c.get_path(&baum, &url("Tree"))).unwrap_err();
+        assert!(matches!(
+            e.downcast_ref(),
+            Some(CollectionErr::MixedDomains(_, _))
+        ));
+        assert_eq!(fetcher.requests(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn find_path_fails_without_path_in_neighbourhood() {
+        let fetcher = Arc::new(MockFetcher::graph(&[("A", &["B"]), ("B", &[]), ("C", &[])]));
+        let mut c = Collector::builder().fetcher(fetcher).build();
+        for (tg, ns) in [
+            ("C", vec![url("A"), url("B")]),
+            ("C", vec![url("A"), url("B"), url("C")]),
+        ]
+        .iter()
+        {
+            let e = block_on(c.find_path(&url("A"), &url(tg), ns.clone())).unwrap_err();
+            assert!(matches!(
+                e.downcast_ref(),
+                Some(CollectionErr::PathFindingError)
+            ));
+        }
+    }
+
+    #[test]
+    fn backward_search_is_updated_incrementally() {
+        let refs = |names: &[&str]| names.iter().map(|n| url(n)).collect::<HashSet<_>>();
+        let mut b = BackwardSearch::new(&url("T"));
+        // A is added before anything it links to is known.
+        b.add(&url("A"), &refs(&["C", "B"]));
+        b.add(&url("C"), &refs(&["D"]));
+        b.add(&url("D"), &refs(&["T"]));
+        assert_eq!(b.next[&url("A")], (3, Some(url("C"))));
+        // B is a shortcut for A. Articles are only added once.
+        b.add(&url("B"), &refs(&["T"]));
+        assert_eq!(b.next[&url("A")], (2, Some(url("B"))));
+        b.add(&url("C"), &refs(&["T"]));
+        assert_eq!(b.next[&url("C")], (2, Some(url("D"))));
+        // The smallest of the next articles wins.
+        b.add(&url("E"), &refs(&["D", "B"]));
+        assert_eq!(b.next[&url("E")], (2, Some(url("B"))));
+        assert!(!b.next.contains_key(&url("X")));
+    }
+
+    #[test]
+    fn bidirectional_search_meets_known_articles() -> Result<(), Box<dyn Error>> {
+        let fetcher = Arc::new(MockFetcher::graph(&[
+            ("A", &["B", "X"]),
+            ("B", &["C"]),
+            ("C", &["D"]),
+            ("D", &["E"]),
+            ("E", &["T"]),
+            ("T", &[]),
+            ("X", &[]),
+        ]));
+        let mut c = Collector::builder().fetcher(fetcher.clone()).build();
+        let names = |p: Vec<Article>| p.iter().map(|a| a.url.get_name()).collect::<Vec<_>>();
+        // Nothing is known about T yet, so this is a plain breadth-first search.
+        let path = block_on(c.get_path_bidirectional(&url("D"), &url("T")))?;
+        assert_eq!(names(path), vec!["D", "E", "T"]);
+        assert_eq!(fetcher.requests(), 3);
+        // D and E are known to lead to T, so the search from A stops at C.
+        let path = block_on(c.get_path_bidirectional(&url("A"), &url("T")))?;
+        assert_eq!(names(path), vec!["A", "B", "C", "D", "E", "T"]);
+        assert_eq!(fetcher.requests(), 7);
+        let e = block_on(c.get_path_bidirectional(&url("X"), &url("T"))).unwrap_err();
+        assert!(matches!(
+            e.downcast_ref(),
+            Some(CollectionErr::PathFindingError)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn progress_is_reported_through_events() -> Result<(), Box<dyn Error>> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let mut c = Collector::builder()
+            .fetcher(MockFetcher::graph(&[
+                ("A", &["B"]),
+                ("B", &["C"]),
+                ("C", &["A"]),
+            ]))
+            .on_progress(move |e| sink.lock().unwrap().push(e))
+            .build();
+        block_on(c.get(&url("A")))?;
+        block_on(c.get_list(&[url("A"), url("B")]))?;
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ProgressEvent::Fetched(url("A")),
+                ProgressEvent::Fetched(url("B")),
+                ProgressEvent::ListCompleted {
+                    requested: 2,
+                    cache_hits: 1
+                },
+            ]
+        );
+        events.lock().unwrap().clear();
+        block_on(c.all_pairs_paths(&[url("A"), url("C")], &[]))?;
+        let events = events.lock().unwrap();
+        assert!(events.contains(&ProgressEvent::PathFound {
+            from: url("A"),
+            to: url("C"),
+            length: 2
+        }));
+        assert!(events.contains(&ProgressEvent::Fetched(url("C"))));
+        Ok(())
+    }
+
+    #[test]
+    fn stats_count_cache_hits() -> Result<(), Box<dyn Error>> {
+        let fetcher =
+            Arc::new(MockFetcher::graph(&[("A", &["B"]), ("B", &[])]).with_status("C", 503));
+        let mut c = Collector::builder()
+            .fetcher(fetcher.clone())
+            .retry_policy(RetryPolicy::uniform(1, Duration::from_millis(1)))
+            .build();
+        assert_eq!(c.stats(), CollectorStats::default());
+        block_on(c.get(&url("A")))?;
+        block_on(c.get(&url("A")))?;
+        block_on(c.get_list(&[url("A"), url("B"), url("B")]))?;
+        assert!(block_on(c.get_list(&[url("C")])).is_err());
+        block_on(c.get_list(&[url("A")]))?;
+        let stats = c.stats();
+        assert_eq!(
+            stats,
+            CollectorStats {
+                lookups: 7,
+                // The request for C was repeated once.
+                processed: 4,
+                cache_hits: 4,
+                cache_misses: 3,
+                cache_size: 2,
+            }
+        );
+        assert_eq!(fetcher.requests(), stats.processed);
+        assert_eq!(stats.hit_rate(), 4.0 / 7.0);
+        Ok(())
+    }
+
+    #[test]
+    fn least_recently_used_articles_are_evicted() -> Result<(), Box<dyn Error>> {
+        let fetcher = Arc::new(MockFetcher::graph(&[
+            ("A", &[]),
+            ("B", &[]),
+            ("C", &[]),
+            ("D", &[]),
+        ]));
+        let mut c = Collector::builder()
+            .fetcher(fetcher.clone())
+            .cache_capacity(2)
+            .build();
+        block_on(c.get(&url("A")))?;
+        block_on(c.get(&url("B")))?;
+        block_on(c.get(&url("A")))?;
+        block_on(c.get(&url("C")))?;
+        assert_eq!(c.stats().cache_size, 2);
+        // B was used least recently, so it had to make room for C.
+        assert_eq!(fetcher.requests(), 3);
+        block_on(c.get_list(&[url("A"), url("C")]))?;
+        assert_eq!(fetcher.requests(), 3);
+        block_on(c.get(&url("B")))?;
+        assert_eq!(fetcher.requests(), 4);
+        block_on(c.get_list(&[url("D")]))?;
+        assert_eq!(c.stats().cache_size, 2);
+        let names: HashSet<_> = c.cached_articles().into_iter().map(|a| a.url).collect();
+        assert_eq!(names, [url("B"), url("D")].iter().cloned().collect());
+        c.clear_cache();
+        assert_eq!(c.stats().cache_size, 0);
+        // Even the smallest cache keeps the article that was received last.
+        let mut d = Collector::builder()
+            .fetcher(MockFetcher::graph(&[
+                ("A", &["B"]),
+                ("B", &["C"]),
+                ("C", &[]),
+            ]))
+            .cache_capacity(0)
+            .build();
+        assert_eq!(block_on(d.get_path(&url("A"), &url("C")))?.len(), 3);
+        assert_eq!(d.stats().cache_size, 1);
+        block_on(c.get(&url("D")))?;
+        assert_eq!(fetcher.requests(), 6);
+        Ok(())
+    }
+
+    #[test]
+    fn evicted_articles_are_still_saved() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join(format!("wikigraph-{}-evicted", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+        let mut c = Collector::builder()
+            .fetcher(MockFetcher::graph(&[("A", &[]), ("B", &[]), ("C", &[])]))
+            .cache_capacity(1)
+            .build();
+        block_on(c.get_list(&[url("A"), url("B"), url("C")]))?;
+        assert_eq!(c.stats().cache_size, 1);
+        assert_eq!(c.save_cache(path)?, 3);
+        assert_eq!(c.save_cache(path)?, 0);
+        let (arts, _) = cache::read_articles(path)?;
+        std::fs::remove_file(path)?;
+        let names: HashSet<_> = arts.into_iter().map(|a| a.url).collect();
+        assert_eq!(
+            names,
+            [url("A"), url("B"), url("C")].iter().cloned().collect()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn disambiguation_seeds_are_reported() -> Result<(), Box<dyn Error>> {
+        let mercury = page(&["Mercury_(planet)", "Mercury_(element)"])
+            + "<div id=\"catlinks\"><a href=\"/wiki/Category:All_disambiguation_pages\">All</a></div>\n";
+        let fetcher = Arc::new(
+            MockFetcher::graph(&[
+                ("Venus", &["Mercury"]),
+                ("Mercury_(planet)", &[]),
+                ("Mercury_(element)", &[]),
+            ])
+            .with_page(&url("Mercury").to_string(), &mercury),
+        );
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let mut c = Collector::builder()
+            .fetcher(fetcher.clone())
+            .on_progress(move |e| {
+                if let ProgressEvent::DisambiguationSeed(_) = e {
+                    sink.lock().unwrap().push(e)
+                }
+            })
+            .build();
+        let arts = block_on(c.get_neighbourhood(&url("Mercury"), 1))?;
+        assert_eq!(arts.len(), 3);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![ProgressEvent::DisambiguationSeed(url("Mercury"))]
+        );
+        // Intermediate disambiguation pages are not reported.
+        block_on(c.get_neighbourhood(&url("Venus"), 2))?;
+        assert_eq!(events.lock().unwrap().len(), 1);
+        // Streaming the neighbourhood reports the seed as well.
+        let streamed = block_on(
+            c.get_neighbourhood_stream(&url("Mercury"), 1)
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(streamed.len(), 3);
+        assert_eq!(events.lock().unwrap().len(), 2);
+        let mut c = Collector::builder()
+            .fetcher(fetcher.clone())
+            .disambiguation_seeds(DisambiguationSeeds::Fail)
+            .build();
+        let streamed = block_on(
+            c.get_neighbourhood_stream(&url("Mercury"), 1)
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(streamed.len(), 1);
+        assert!(matches!(
+            streamed[0].as_ref().unwrap_err().downcast_ref(),
+            Some(CollectionErr::DisambiguationSeed(_))
+        ));
+        let e = block_on(c.get_path(&url("Mercury"), &url("Mercury_(planet)"))).unwrap_err();
+        assert!(matches!(
+            e.downcast_ref(),
+            Some(CollectionErr::DisambiguationSeed(u)) if *u == url("Mercury")
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn concurrency_is_limited() -> Result<(), Box<dyn Error>> {
+        let names: Vec<String> = (0..20).map(|i| format!("P{}", i)).collect();
+        let edges: Vec<(&str, &[&str])> = names.iter().map(|n| (n.as_str(), &[][..])).collect();
+        let slow = || {
+            names.iter().fold(MockFetcher::graph(&edges), |m, n| {
+                m.with_delay(n, Duration::from_millis(5))
+            })
+        };
+        let mut urls: Vec<URL> = names.iter().map(|n| url(n)).collect();
+        urls.push(url("P0"));
+        let fetcher = Arc::new(slow());
+        let mut c = Collector::builder()
+            .concurrency(3)
+            .fetcher(fetcher.clone())
+            .build();
+        assert_eq!(block_on(c.get_list(&urls))?.len(), 21);
+        assert_eq!(fetcher.requests(), 20);
+        assert_eq!(fetcher.max_in_flight(), 3);
+        let fetcher = Arc::new(slow());
+        let mut c = Collector::builder().fetcher(fetcher.clone()).build();
+        block_on(c.get_list(&urls))?;
+        assert_eq!(fetcher.max_in_flight(), DEFAULT_CONCURRENCY);
+        Ok(())
+    }
+
+    #[test]
+    fn backpressure_slows_down_on_errors() -> Result<(), Box<dyn Error>> {
+        let names: Vec<String> = (0..16).map(|i| format!("P{}", i)).collect();
+        let edges: Vec<(&str, &[&str])> = names.iter().map(|n| (n.as_str(), &[][..])).collect();
+        // The first 8 articles are rate-limited.
+        let fetcher = Arc::new(
+            names[..8]
+                .iter()
+                .fold(MockFetcher::graph(&edges), |m, n| m.with_status(n, 429)),
+        );
+        let mut c = Collector::builder()
+            .fetcher(fetcher.clone())
+            .retry_policy(RetryPolicy::default())
+            .backpressure(Backpressure {
+                window: 4,
+                error_threshold: 0.5,
+                decrease: 0.5,
+                min_concurrency: 1,
+            })
+            .build();
+        let mut limits = Vec::new();
+        for n in names.iter() {
+            let _ = block_on(c.get(&url(n)));
+            limits.push(c.concurrency_limit());
+        }
+        assert_eq!(limits, vec![8, 8, 8, 4, 4, 4, 4, 2, 2, 2, 2, 3, 3, 3, 3, 4]);
+        Ok(())
+    }
+
+    #[test]
+    fn rate_limit_spaces_out_requests() -> Result<(), Box<dyn Error>> {
+        let names: Vec<String> = (0..5).map(|i| format!("P{}", i)).collect();
+        let edges: Vec<(&str, &[&str])> = names.iter().map(|n| (n.as_str(), &[][..])).collect();
+        let urls: Vec<URL> = names.iter().map(|n| url(n)).collect();
+        let mut c = Collector::builder()
+            .fetcher(MockFetcher::graph(&edges))
+            .rate_limit(Duration::from_millis(30))
+            .build();
+        let start = std::time::Instant::now();
+        assert_eq!(block_on(c.get_list(&urls))?.len(), 5);
+        // All of them could be in flight at once, but they have to start one by one.
+        assert!(start.elapsed() >= Duration::from_millis(120));
+        Ok(())
+    }
+
+    #[test]
+    fn redirects_are_collapsed() -> Result<(), Box<dyn Error>> {
+        let usa = format!(
+            "<link rel=\"canonical\" href=\"{}\"/>\n{}",
+            url("United_States"),
+            page(&["B"])
+        );
+        let fetcher = Arc::new(
+            MockFetcher::graph(&[("A", &["USA", "B"]), ("B", &[])])
+                .with_page(&url("USA").to_string(), &usa),
+        );
+        let mut c = Collector::builder().fetcher(fetcher.clone()).build();
+        assert_eq!(block_on(c.get(&url("USA")))?.url, url("United_States"));
+        assert_eq!(
+            block_on(c.get(&url("United_States")))?.url,
+            url("United_States")
+        );
+        assert_eq!(fetcher.requests(), 1);
+        assert_eq!(c.redirects_followed(), 1);
+        // The article is only counted once, even though it can be found under both URLs.
+        assert_eq!(
+            c.estimated_memory(),
+            c.cached_articles()[0].estimated_size()
+        );
+        let mut arts = block_on(c.get_neighbourhood(&url("A"), 2))?;
+        arts.sort_by(|a, b| a.url.cmp(&b.url));
+        let names: Vec<_> = arts.iter().map(|a| a.url.get_name()).collect();
+        assert_eq!(names, vec!["A", "B", "United States"]);
+        assert!(arts[0].references.contains(&url("United_States")));
+        assert!(!arts[0].references.contains(&url("USA")));
+        assert_eq!(c.cached_articles().len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn path_subgraph_only_contains_shortest_paths() -> Result<(), Box<dyn Error>> {
+        let mut c = Collector::builder()
+            .fetcher(MockFetcher::graph(&[
+                ("A", &["B", "C", "D"]),
+                ("B", &["T", "E"]),
+                ("C", &["T"]),
+                ("D", &["E"]),
+                ("E", &["T"]),
+                ("T", &["A"]),
+            ]))
+            .build();
+        let g = block_on(c.get_path_subgraph(&url("A"), &url("T")))?;
+        assert_eq!(g.nodes(), vec![&url("A"), &url("B"), &url("C"), &url("T")]);
+        assert_eq!(g.edge_count(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn results_do_not_depend_on_the_cache() -> Result<(), Box<dyn Error>> {
+        let edges: &[(&str, &[&str])] = &[
+            ("A", &["B", "C", "D"]),
+            ("B", &["T", "E"]),
+            ("C", &["T"]),
+            ("D", &["E"]),
+            ("E", &["T"]),
+            ("T", &["A"]),
+        ];
+        // Nothing but the article that was received last stays in the cache.
+        let mut c = Collector::builder()
+            .fetcher(MockFetcher::graph(edges))
+            .cache_capacity(1)
+            .build();
+        let arts = block_on(c.get_list(&[url("A"), url("B"), url("A"), url("C")]))?;
+        assert_eq!(arts.len(), 4);
+        let g = block_on(c.get_path_subgraph(&url("A"), &url("T")))?;
+        assert_eq!(g.nodes(), vec![&url("A"), &url("B"), &url("C"), &url("T")]);
+        assert_eq!(g.edge_count(), 4);
+        Ok(())
+    }
+
+    /// What a mirror serves instead of an article after too many requests.
+    const LOGIN_INTERSTITIAL: &str = r#"<!DOCTYPE html>
+<html class="client-nojs" lang="en" dir="ltr">
+<head>
+<meta charset="UTF-8"/>
+<title>Log in - Wikipedia</title>
+<script>RLCONF={"wgCanonicalNamespace":"Special","wgCanonicalSpecialPageName":"Userlogin","wgTitle":"UserLogin"};</script>
+</head>
+<body>
+<div id="mw-content-text"><p>You have made too many requests. Please <a href="/wiki/Special:UserLogin">log in</a> to continue.</p></div>
+<a href="/wiki/Main_Page">Main Page</a>
+</body>
+</html>
+"#;
+
+    #[test]
+    fn rate_limit_pages_are_not_parsed() {
+        let fetcher = Arc::new(
+            MockFetcher::graph(&[("A", &["B"])])
+                .with_page(&url("B").to_string(), LOGIN_INTERSTITIAL),
+        );
+        let mut c = Collector::builder()
+            .fetcher(fetcher.clone())
+            .retry_policy(RetryPolicy::default().with(
+                FailureClass::TooManyRequests,
+                Backoff::constant(2, Duration::from_millis(0)),
+            ))
+            .build();
+        assert!(Article::parse(url("B"), String::from(LOGIN_INTERSTITIAL))
+            .unwrap()
+            .references
+            .is_empty());
+        let e = block_on(c.get(&url("B"))).unwrap_err();
+        match e.downcast_ref::<CollectionErr>() {
+            Some(CollectionErr::RateLimited(u)) => assert_eq!(u, &url("B").to_string()),
+            _ => panic!("unexpected error: {}", e),
+        }
+        assert_eq!(fetcher.requests(), 3);
+        assert_eq!(block_on(c.get(&url("A"))).unwrap().references.len(), 1);
+        let mut c = Collector::builder()
+            .fetcher(fetcher.clone())
+            .rate_limit_markers(&[])
+            .build();
+        assert!(block_on(c.get(&url("B"))).unwrap().references.is_empty());
+    }
+
+    #[test]
+    fn retries_back_off_per_failure_class() -> Result<(), Box<dyn Error>> {
+        let fetcher = Arc::new(
+            MockFetcher::graph(&[("A", &[]), ("B", &[]), ("C", &[]), ("D", &[])])
+                .with_status("A", 429)
+                .with_status("B", 503)
+                .with_failure("C", FetchErr::Request(String::from("reset")))
+                .with_status("D", 404),
+        );
+        let ms = Duration::from_millis;
+        let policy = RetryPolicy::default()
+            .with(FailureClass::TooManyRequests, Backoff::constant(1, ms(60)))
+            .with(FailureClass::ServerError, Backoff::constant(2, ms(15)))
+            .with(FailureClass::Connection, Backoff::constant(3, ms(0)));
+        let mut c = Collector::builder()
+            .fetcher(fetcher.clone())
+            .retry_policy(policy)
+            .build();
+        let mut attempt = |name| {
+            let before = fetcher.requests();
+            let start = Instant::now();
+            assert!(block_on(c.get(&url(name))).is_err());
+            (fetcher.requests() - before, start.elapsed())
+        };
+        let (requests, waited) = attempt("A");
+        assert_eq!(requests, 2);
+        assert!(waited >= ms(60));
+        let (requests, waited) = attempt("B");
+        assert_eq!(requests, 3);
+        assert!(waited >= ms(30));
+        assert_eq!(attempt("C").0, 4);
+        assert_eq!(attempt("D").0, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn transient_failures_are_retried_by_default() -> Result<(), Box<dyn Error>> {
+        let fetcher = Arc::new(MockFetcher::graph(&[("A", &[])]).with_status("A", 404));
+        let mut c = Collector::builder().fetcher(fetcher.clone()).build();
+        assert_eq!(
+            c.retry_policy.backoff(FailureClass::Connection),
+            Some(Backoff::exponential(DEFAULT_RETRIES, DEFAULT_RETRY_DELAY))
+        );
+        let start = Instant::now();
+        assert!(block_on(c.get(&url("A"))).is_err());
+        assert_eq!(fetcher.requests(), 1);
+        assert!(start.elapsed() < DEFAULT_RETRY_DELAY);
+        Ok(())
+    }
+
+    #[test]
+    fn timeouts_are_reported() {
+        let fetcher =
+            Arc::new(MockFetcher::graph(&[("A", &[])]).with_failure("A", FetchErr::Timeout));
+        let mut c = Collector::builder()
+            .fetcher(fetcher.clone())
+            .retry_policy(RetryPolicy::uniform(2, Duration::from_millis(1)))
+            .build();
+        let e = block_on(c.get(&url("A"))).unwrap_err();
+        assert!(matches!(
+            e.downcast_ref(),
+            Some(CollectionErr::Timeout(u)) if *u == url("A").to_string()
+        ));
+        assert_eq!(fetcher.requests(), 3);
+    }
+
+    #[test]
+    fn lenient_list_keeps_successes() -> Result<(), Box<dyn Error>> {
+        let fetcher =
+            Arc::new(MockFetcher::graph(&[("A", &["B"]), ("B", &["A"])]).with_status("B", 503));
+        let mut c = Collector::builder()
+            .fetcher(fetcher.clone())
+            .retry_policy(RetryPolicy::default())
+            .build();
+        let urls = vec![url("A"), url("B"), url("C"), url("C")];
+        assert!(block_on(c.get_list(&urls)).is_err());
+        let (arts, failed) = block_on(c.get_list_lenient(&urls))?;
+        assert_eq!(arts, vec![c.cache[&url("A")].clone()]);
+        let mut failed: Vec<_> = failed
+            .into_iter()
+            .map(|(u, e)| (u.get_name(), e.to_string()))
+            .collect();
+        failed.sort();
+        assert_eq!(
+            failed,
+            vec![
+                (String::from("B"), FetchErr::Status(503).to_string()),
+                (String::from("C"), FetchErr::Status(404).to_string()),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn seed_subgraph_only_has_intra_seed_edges() -> Result<(), Box<dyn Error>> {
+        let fetcher = Arc::new(MockFetcher::graph(&[
+            ("A", &["B", "X"]),
+            ("B", &["C", "Y"]),
+            ("C", &["A", "B", "Z"]),
+            ("D", &["X"]),
+            ("X", &["D"]),
+        ]));
+        let mut c = Collector::builder().fetcher(fetcher.clone()).build();
+        let seeds = vec![url("A"), url("B"), url("C"), url("D")];
+        let arts = block_on(c.get_seed_subgraph(&seeds))?;
+        assert_eq!(fetcher.requests(), 4);
+        let g = Graph::new(&arts);
+        assert_eq!(g.len(), 4);
+        assert_eq!(g.edge_count(), 4);
+        for a in arts.iter() {
+            assert!(a.references.iter().all(|r| seeds.contains(r)));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn query_strings_share_cache_entry() -> Result<(), Box<dyn Error>> {
+        let site = "<a href=\"/wiki/Foo?utm_source=x\">foo</a> <a href=\"/wiki/Foo\">foo</a>";
+        let a = Article::parse(url("A"), content(site))?;
+        assert_eq!(a.references.len(), 1);
+        let fetcher = Arc::new(MockFetcher::graph(&[("Foo", &[])]));
+        let mut c = Collector::builder().fetcher(fetcher.clone()).build();
+        let urls = vec![URL::new("/wiki/Foo?foo=bar")?, URL::new("/wiki/Foo")?];
+        block_on(c.get_list(&urls))?;
+        block_on(c.get(&URL::new("https://en.wikipedia.org/wiki/Foo?action=view")?))?;
+        assert_eq!(c.cached_articles().len(), 1);
+        assert_eq!(fetcher.requests(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn builder_applies_http_options() {
+        let b = Collector::builder()
+            .http2_prior_knowledge(true)
+            .pool_idle_timeout(Duration::from_secs(30))
+            .tcp_keepalive(Duration::from_secs(60))
+            .tcp_nodelay(false)
+            .user_agent("wikigraph/0.1 (contact@example.com)")
+            .timeout(Duration::from_secs(10));
+        let expected = HttpOptions {
+            http2_prior_knowledge: true,
+            pool_idle_timeout: Some(Duration::from_secs(30)),
+            tcp_keepalive: Some(Duration::from_secs(60)),
+            tcp_nodelay: false,
+            user_agent: String::from("wikigraph/0.1 (contact@example.com)"),
+            timeout: Some(Duration::from_secs(10)),
+        };
+        assert_eq!(b.http, expected);
+        let client = format!("{:?}", b.http.client_builder());
+        assert!(client.contains("http2_prior_knowledge: true"));
+        b.build();
+    }
+
+    #[test]
+    fn memory_limit_stops_collection() -> Result<(), Box<dyn Error>> {
+        let edges: &[(&str, &[&str])] = &[
+            ("A", &["Alpha", "Beta"]),
+            ("B", &["Gamma", "Delta"]),
+            ("C", &["Epsilon", "Zeta"]),
+            ("D", &[]),
+        ];
+        let mut c = Collector::builder()
+            .fetcher(MockFetcher::graph(edges))
+            .memory_limit(30)
+            .build();
+        assert_eq!(block_on(c.get(&url("A")))?.estimated_size(), 10);
+        block_on(c.get(&url("B")))?;
+        assert_eq!(c.estimated_memory(), 21);
+        let e = block_on(c.get(&url("C"))).unwrap_err();
+        assert!(matches!(
+            e.downcast_ref::<CollectionErr>(),
+            Some(CollectionErr::MemoryLimit(33))
+        ));
+        assert_eq!(c.cached_articles().len(), 3);
+        let e = block_on(c.get_list(&[url("D")])).unwrap_err();
+        assert!(e.downcast_ref::<CollectionErr>().is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn saving_twice_only_writes_new_articles() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join(format!("wikigraph-{}-save", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+        let fetcher = Arc::new(MockFetcher::graph(&[
+            ("A", &["B"]),
+            ("B", &["C"]),
+            ("C", &[]),
+        ]));
+        let mut c = Collector::builder().fetcher(fetcher.clone()).build();
+        block_on(c.get_list(&[url("A"), url("B")]))?;
+        assert_eq!(c.save_cache(path)?, 2);
+        block_on(c.get(&url("B")))?;
+        block_on(c.get(&url("C")))?;
+        assert_eq!(c.save_cache(path)?, 1);
+        assert_eq!(c.save_cache(path)?, 0);
+        assert_eq!(std::fs::read_to_string(path)?.lines().count(), 3);
+        let mut d = Collector::builder().fetcher(fetcher.clone()).build();
+        assert_eq!(d.load_cache(path)?, 3);
+        assert_eq!(d.save_cache(path)?, 0);
+        std::fs::remove_file(path)?;
+        block_on(d.get(&url("C")))?;
+        assert_eq!(fetcher.requests(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn time_limited_crawl_resumes_to_same_result() -> Result<(), Box<dyn Error>> {
+        let path =
+            std::env::temp_dir().join(format!("wikigraph-{}-checkpoint", std::process::id()));
+        let path = path.to_str().unwrap();
+        let edges: &[(&str, &[&str])] = &[
+            ("A", &["B", "C"]),
+            ("B", &["D"]),
+            ("C", &["E"]),
+            ("D", &["F"]),
+            ("E", &[]),
+            ("F", &[]),
+        ];
+        let sorted = |mut arts: Vec<Article>| {
+            arts.sort_by(|a, b| a.url.cmp(&b.url));
+            arts
+        };
+        let mut c = Collector::builder()
+            .fetcher(MockFetcher::graph(edges))
+            .build();
+        let expected = sorted(block_on(c.get_neighbourhood(&url("A"), 3))?);
+        assert_eq!(expected.len(), 6);
+
+        let slow = Arc::new(MockFetcher::graph(edges).with_delay("D", Duration::from_secs(5)));
+        let mut c = Collector::builder()
+            .fetcher(slow.clone())
+            .time_limit(Duration::from_millis(100), path)
+            .build();
+        let e = block_on(c.get_neighbourhood(&url("A"), 3)).unwrap_err();
+        match e.downcast_ref::<CollectionErr>() {
+            Some(CollectionErr::TimeLimitExceeded(p)) => assert_eq!(p, path),
+            _ => panic!("unexpected error: {}", e),
+        }
+        let cp = Checkpoint::load(path)?;
+        assert_eq!(cp.depth, 1);
+        let mut frontier = cp.frontier.clone();
+        frontier.sort();
+        assert_eq!(frontier, vec![url("D"), url("E")]);
+        assert_eq!(cp.seen.len(), 5);
+        assert_eq!(cp.articles.len(), 4);
+
+        let fetcher = Arc::new(MockFetcher::graph(edges));
+        let mut d = Collector::builder()
+            .fetcher(fetcher.clone())
+            .time_limit(Duration::from_secs(60), path)
+            .build();
+        let resumed = sorted(block_on(d.resume_neighbourhood(path))?);
+        std::fs::remove_file(path)?;
+        assert_eq!(resumed, expected);
+        // Only D and F were missing from the checkpoint.
+        assert_eq!(fetcher.requests(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn reparsing_uses_current_parser() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join(format!("wikigraph-{}-reparse", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+        let html = "<div id=\"content\"><a href=\"/wiki/B\">B</a></div>\n\
+                    <div id=\"footer\"><a href=\"/wiki/C\">C</a></div>\n";
+        let fetcher = Arc::new(MockFetcher::default().with_page(&url("A").to_string(), html));
+        let mut c = Collector::builder()
+            .fetcher(fetcher.clone())
+            .parse_backend(ParseBackend::Scan)
+            .retain_html(true)
+            .build();
+        let a = block_on(c.get(&url("A")))?;
+        assert_eq!(a.references.len(), 2);
+        assert_eq!(a.html.as_deref(), Some(html));
+        c.save_cache(path)?;
+        let mut d = Collector::builder()
+            .fetcher(fetcher.clone())
+            .parse_backend(ParseBackend::html("#content")?)
+            .build();
+        d.load_cache(path)?;
+        std::fs::remove_file(path)?;
+        assert_eq!(d.reparse_all()?, 1);
+        let a = block_on(d.get(&url("A")))?;
+        assert_eq!(a.references, vec![url("B")].into_iter().collect());
+        assert_eq!(fetcher.requests(), 1);
+        let mut e = Collector::builder().fetcher(fetcher.clone()).build();
+        assert!(block_on(e.get(&url("A")))?.html.is_none());
+        assert_eq!(e.reparse_all()?, 0);
+        // An article that was requested through a redirect is only parsed once.
+        let usa = format!(
+            "<link rel=\"canonical\" href=\"{}\"/>\n{}",
+            url("United_States"),
+            page(&["B"])
+        );
+        let mut c = Collector::builder()
+            .fetcher(MockFetcher::default().with_page(&url("USA").to_string(), &usa))
+            .retain_html(true)
+            .build();
+        block_on(c.get(&url("USA")))?;
+        assert_eq!(c.save_cache(path)?, 1);
+        assert_eq!(c.reparse_all()?, 1);
+        assert_eq!(c.save_cache(path)?, 1);
+        std::fs::remove_file(path)?;
+        assert_eq!(block_on(c.get(&url("USA")))?.url, url("United_States"));
+        assert_eq!(c.cached_articles().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn dropping_collector_persists_cache() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join(format!("wikigraph-{}-drop", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+        let fetcher = Arc::new(MockFetcher::graph(&[("A", &["B"]), ("B", &[])]));
+        let mut c = Collector::builder()
+            .fetcher(fetcher.clone())
+            .persist_on_drop(path)
+            .build();
+        block_on(c.get_list(&[url("A"), url("B")]))?;
+        drop(c);
+        let mut d = Collector::builder().fetcher(fetcher.clone()).build();
+        assert_eq!(d.load_cache(path)?, 2);
+        std::fs::remove_file(path)?;
+        drop(d);
+        assert!(std::fs::metadata(path).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn all_pairs_paths_skips_prior_results() -> Result<(), Box<dyn Error>> {
+        let edges: &[(&str, &[&str])] =
+            &[("A", &["B", "C"]), ("B", &["A", "C"]), ("C", &["A", "B"])];
+        let urls = vec![url("A"), url("B"), url("C")];
+        let mut c = Collector::builder()
+            .fetcher(MockFetcher::graph(edges))
+            .build();
+        let all = block_on(c.all_pairs_paths(&urls, &[]))?;
+        assert_eq!(all.len(), 6);
+        // Nothing is recomputed if everything has been done already.
+        let m = Arc::new(MockFetcher::graph(edges));
+        let mut c = Collector::builder().fetcher(m.clone()).build();
+        assert_eq!(block_on(c.all_pairs_paths(&urls, &all))?, all);
+        assert_eq!(m.requests(), 0);
+        // Prior results are kept as they are, even if they would look different now.
+        let prior = vec![PathResult {
+            from: url("A"),
+            to: url("B"),
+            path: vec![],
+        }];
+        let res = block_on(c.all_pairs_paths(&urls, &prior))?;
+        assert_eq!(res.len(), 6);
+        assert_eq!(res[0], prior[0]);
+        assert_eq!(
+            res.iter()
+                .filter(|r| r.is_for(&url("A"), &url("B")))
+                .count(),
+            1
+        );
+        Ok(())
+    }
+
+    const EXTRACT_RESPONSE: &str = r#"{"batchcomplete":true,"query":{"pages":[{"pageid":30806,"ns":0,"title":"Tree","extract":"In botany, a tree is a perennial plant with an elongated stem.\n\n\n== Definition ==\nTrees are not a taxonomic group."}]}}"#;
+
+    #[test]
+    fn parse_extract_returns_plain_text() -> Result<(), Box<dyn Error>> {
+        let text = Article::parse_extract(EXTRACT_RESPONSE)?.unwrap();
+        assert!(text.starts_with("In botany, a tree is a perennial plant"));
+        assert!(text.contains("== Definition ==\nTrees"));
+        let missing =
+            r#"{"batchcomplete":true,"query":{"pages":[{"ns":0,"title":"Nope","missing":true}]}}"#;
+        assert_eq!(Article::parse_extract(missing)?, None);
+        assert!(Article::parse_extract("{}").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn links_can_be_requested_from_the_api() -> Result<(), Box<dyn Error>> {
+        let trees = url("Trees");
+        let cont = vec![
+            (String::from("continue"), String::from("||")),
+            (String::from("plcontinue"), String::from("30806|0|Plant")),
+        ];
+        let first = r#"{"continue":{"plcontinue":"30806|0|Plant","continue":"||"},"query":{"redirects":[{"from":"Trees","to":"Tree"}],"pages":[{"pageid":30806,"ns":0,"title":"Tree","links":[{"ns":0,"title":"Botany"},{"ns":0,"title":"Perennial plant"}]}]}}"#;
+        let second = r#"{"batchcomplete":true,"query":{"redirects":[{"from":"Trees","to":"Tree"}],"pages":[{"pageid":30806,"ns":0,"title":"Tree","links":[{"ns":0,"title":"Plant"}],"categories":[{"ns":14,"title":"Category:Trees"}]}]}}"#;
+        let fetcher = Arc::new(
+            MockFetcher::default()
+                .with_page(&Article::links_url(&trees, &Vec::new()), first)
+                .with_page(&Article::links_url(&trees, &cont), second),
+        );
+        let mut c = Collector::builder()
+            .fetcher(fetcher.clone())
+            .links_api(true)
+            .build();
+        let a = block_on(c.get(&trees))?;
+        assert_eq!(fetcher.requests(), 2);
+        assert_eq!(a.url, url("Tree"));
+        let mut refs: Vec<_> = a.references.iter().map(|r| r.get_name()).collect();
+        refs.sort();
+        assert_eq!(refs, vec!["Botany", "Perennial plant", "Plant"]);
+        assert!(a.categories.contains("Trees"));
+        assert_eq!(c.redirects_followed(), 1);
+        let single = Article::from_api(url("Tree"), second)?;
+        assert_eq!(single.references.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn reverse_links_follow_continuations() -> Result<(), Box<dyn Error>> {
+        let tree = url("Tree");
+        let cont = vec![
+            (String::from("blcontinue"), String::from("0|1234")),
+            (String::from("continue"), String::from("-||")),
+        ];
+        let first = r#"{"continue":{"blcontinue":"0|1234","continue":"-||"},"query":{"backlinks":[{"pageid":1,"ns":0,"title":"Forest"},{"pageid":2,"ns":0,"title":"Plant"}]}}"#;
+        let second = r#"{"batchcomplete":true,"query":{"backlinks":[{"pageid":3,"ns":0,"title":"Oak tree"},{"pageid":4,"ns":0,"title":"Forest"}]}}"#;
+        let fetcher = Arc::new(
+            MockFetcher::default()
+                .with_page(&Article::backlinks_url(&tree, &Vec::new()), first)
+                .with_page(&Article::backlinks_url(&tree, &cont), second),
+        );
+        let mut c = Collector::builder().fetcher(fetcher.clone()).build();
+        let links = block_on(c.get_reverse_links(&tree))?;
+        let mut names: Vec<_> = links.iter().map(|u| u.get_name()).collect();
+        names.sort();
+        assert_eq!(names, vec!["Forest", "Oak tree", "Plant"]);
+        assert_eq!(fetcher.requests(), 2);
+        assert!(Article::backlinks_url(&tree, &cont).contains("blcontinue=0%7C1234"));
+        Ok(())
+    }
+
+    #[test]
+    fn content_is_only_fetched_on_request() -> Result<(), Box<dyn Error>> {
+        let tree = url("Tree");
+        let m = MockFetcher::graph(&[("Tree", &["Plant"])])
+            .with_page(&Article::extract_url(&tree), EXTRACT_RESPONSE);
+        let m = Arc::new(m);
+        let mut c = Collector::builder().fetcher(m.clone()).build();
+        assert_eq!(block_on(c.get(&tree))?.content, None);
+        assert_eq!(m.requests(), 1);
+        let mut c = Collector::builder()
+            .fetcher(m.clone())
+            .with_content(true)
+            .build();
+        let a = block_on(c.get(&tree))?;
+        assert!(a.content.unwrap().starts_with("In botany"));
+        assert_eq!(a.references.len(), 1);
+        assert_eq!(m.requests(), 3);
+        Ok(())
+    }
+
+    fn slow_fetcher() -> Arc<MockFetcher> {
+        let edges: &[(&str, &[&str])] = &[("A", &[]), ("B", &[]), ("C", &[]), ("D", &[])];
+        Arc::new(
+            MockFetcher::graph(edges)
+                .with_delay("A", Duration::from_millis(10))
+                .with_delay("B", Duration::from_millis(300))
+                .with_delay("C", Duration::from_millis(300))
+                .with_delay("D", Duration::from_millis(300)),
+        )
+    }
+
+    #[test]
+    fn cancelling_get_list_stops_requests() {
+        let m = slow_fetcher();
+        let mut c = Collector::builder().fetcher(m.clone()).build();
+        let urls = vec![url("A"), url("B"), url("C"), url("D")];
+        let token = CancellationToken::new();
+        let (res, _) = block_on(async {
+            futures::join!(c.get_list_cancellable(&urls, &token), async {
+                tokio::time::delay_for(Duration::from_millis(50)).await;
+                token.cancel();
+            })
+        });
+        let e = res.unwrap_err();
+        assert!(matches!(
+            e.downcast_ref::<CollectionErr>(),
+            Some(CollectionErr::Cancelled)
+        ));
+        block_on(async { tokio::time::delay_for(Duration::from_millis(400)).await });
+        assert_eq!(m.requests(), 4);
+        assert_eq!(m.responses(), 1);
+        // The article that arrived in time is cached.
+        block_on(c.get(&url("A"))).unwrap();
+        assert_eq!(m.requests(), 4);
+    }
+
+    #[test]
+    fn dropping_get_list_stops_requests() {
+        let m = slow_fetcher();
+        let mut c = Collector::builder().fetcher(m.clone()).build();
+        let urls = vec![url("A"), url("B"), url("C"), url("D")];
+        let res = block_on(async {
+            tokio::time::timeout(Duration::from_millis(50), c.get_list(&urls)).await
+        });
+        assert!(res.is_err());
+        block_on(async { tokio::time::delay_for(Duration::from_millis(400)).await });
+        assert_eq!(m.responses(), 1);
+    }
+
+    #[test]
+    fn get_is_deterministic() -> Result<(), Box<dyn Error>> {
+        let mut runtime = tokio::runtime::Builder::new()
+            .basic_scheduler()
+            .threaded_scheduler()
+            .enable_all()
+            .build()
+            .unwrap();
+        let u = URL::new("https://en.wikipedia.org/wiki/Wikipedia")?;
+        let fetcher = Arc::new(MockFetcher::graph(&[(
+            "Wikipedia",
+            &["Tree", "Encyclopedia"],
+        )]));
+        let mut c = Collector::with_fetcher(fetcher.clone());
         let r = runtime.block_on(c.get(&u))?;
+        assert_eq!(r.references.len(), 2);
         for _ in 0..100 {
             assert_eq!(runtime.block_on(c.get(&u))?, r);
         }
+        assert_eq!(fetcher.requests(), 1);
         Ok(())
     }
 
@@ -385,11 +4290,147 @@ mod tests {
             URL::new("https://en.wikipedia.org/wiki/Wikipedia")?,
             URL::new("https://en.wikipedia.org/wiki/Tree")?,
         ];
-        let mut c = Collector::new();
+        let fetcher = MockFetcher::graph(&[("Wikipedia", &["Tree", "Encyclopedia"])])
+            .with_page(&us[1].to_string(), TREE_SNAPSHOT);
+        let mut c = Collector::with_fetcher(fetcher);
         let r = runtime.block_on(c.get_list(&us))?;
+        assert_eq!(r.len(), 2);
         for _ in 0..100 {
             assert_eq!(runtime.block_on(c.get_list(&us))?, r);
         }
         Ok(())
     }
+
+    #[test]
+    fn path_to_itself_is_a_single_article() -> Result<(), Box<dyn Error>> {
+        let fetcher = Arc::new(MockFetcher::graph(&[("A", &["B"]), ("B", &["A"])]));
+        let mut c = Collector::builder().fetcher(fetcher.clone()).build();
+        let path = block_on(c.get_path(&url("A"), &url("A")))?;
+        assert_eq!(path.len(), 1);
+        assert_eq!(path[0].url, url("A"));
+        let paths = block_on(c.get_all_shortest_paths(&url("A"), &url("A"), 5))?;
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].len(), 1);
+        let via = block_on(c.get_path_via(&url("A"), &url("A"), &url("B")))?;
+        let names: Vec<_> = via.iter().map(|a| a.url.get_name()).collect();
+        assert_eq!(names, ["A", "B"]);
+        Ok(())
+    }
+
+    #[test]
+    fn dijkstra_path_contains_both_endpoints() {
+        let adj = adjacency(3, &[(0, 2), (1, 0), (2, 1)]);
+        assert_eq!(binary_dijkstra(&adj, 0, 2), Some(vec![0, 2]));
+        assert_eq!(binary_dijkstra(&adj, 1, 0), Some(vec![1, 0]));
+        assert_eq!(binary_dijkstra(&adj, 1, 1), Some(vec![1]));
+        assert_eq!(binary_dijkstra(&adj, 0, 3), None);
+    }
+
+    #[test]
+    fn politeness_presets_can_be_overridden() {
+        let c = Collector::builder().politeness(Politeness::Polite).build();
+        assert_eq!(c.concurrency_limit(), 2);
+        assert_eq!(
+            c.throttle.as_ref().map(|t| t.policy()),
+            Politeness::Polite.backpressure()
+        );
+        let backoff = c
+            .retry_policy
+            .backoff(FailureClass::TooManyRequests)
+            .unwrap();
+        assert_eq!(backoff.retries, 5);
+        assert_eq!(backoff.delay, Duration::from_secs(10));
+        // Explicit settings win, even if the preset comes later.
+        let c = Collector::builder()
+            .concurrency(4)
+            .retry_policy(RetryPolicy::default())
+            .politeness(Politeness::Polite)
+            .build();
+        assert_eq!(c.concurrency_limit(), 4);
+        assert_eq!(c.retry_policy, RetryPolicy::default());
+        assert!(c.throttle.is_some());
+        let c = Collector::builder()
+            .politeness(Politeness::Aggressive)
+            .build();
+        assert_eq!(c.concurrency_limit(), 32);
+        assert!(c.throttle.is_none());
+    }
+
+    #[test]
+    fn references_can_be_sorted() {
+        let a = &articles(&[("A", &["Zebra", "Cat", "Mouse", "Ant"])])[0];
+        let names: Vec<_> = a.references_sorted().iter().map(|r| r.get_name()).collect();
+        assert_eq!(names, ["Ant", "Cat", "Mouse", "Zebra"]);
+    }
+
+    #[test]
+    fn all_shortest_paths_are_enumerated() -> Result<(), Box<dyn Error>> {
+        let adj = adjacency(6, &[(0, 1), (0, 2), (1, 3), (2, 3), (2, 4), (3, 5), (4, 5)]);
+        assert_eq!(
+            shortest_paths(&adj, 0, 5, 10),
+            vec![vec![0, 1, 3, 5], vec![0, 2, 3, 5], vec![0, 2, 4, 5]]
+        );
+        assert_eq!(shortest_paths(&adj, 0, 5, 2).len(), 2);
+        assert_eq!(binary_dijkstra(&adj, 0, 5), Some(vec![0, 1, 3, 5]));
+        assert!(shortest_paths(&adj, 5, 0, 10).is_empty());
+        let mut c = Collector::builder()
+            .fetcher(MockFetcher::graph(&[
+                ("A", &["B", "C", "T"]),
+                ("B", &["D"]),
+                ("C", &["D", "E"]),
+                ("D", &["F"]),
+                ("E", &["F"]),
+                ("F", &[]),
+                ("T", &[]),
+            ]))
+            .build();
+        let names = |ps: Vec<Vec<Article>>| {
+            ps.into_iter()
+                .map(|p| p.iter().map(|a| a.url.get_name()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+        let paths = block_on(c.get_all_shortest_paths(&url("A"), &url("F"), 10))?;
+        assert_eq!(
+            names(paths),
+            vec![
+                vec!["A", "B", "D", "F"],
+                vec!["A", "C", "D", "F"],
+                vec!["A", "C", "E", "F"]
+            ]
+        );
+        let paths = block_on(c.get_all_shortest_paths(&url("A"), &url("T"), 10))?;
+        assert_eq!(names(paths), vec![vec!["A", "T"]]);
+        Ok(())
+    }
+
+    /// Builds the adjacency lists of a graph with l nodes and the given edges.
+    fn adjacency(l: usize, edges: &[(usize, usize)]) -> Vec<Vec<usize>> {
+        let mut adj = vec![Vec::new(); l];
+        for (u, v) in edges {
+            adj[*u].push(*v);
+        }
+        adj
+    }
+
+    #[test]
+    fn dijkstra_finds_shortest_paths() {
+        // A chain 0 -> 1 -> 2 -> 3 -> 4 with a shortcut 1 -> 3.
+        let adj = adjacency(5, &[(0, 1), (1, 2), (2, 3), (3, 4), (1, 3)]);
+        assert_eq!(binary_dijkstra(&adj, 0, 4), Some(vec![0, 1, 3, 4]));
+        assert_eq!(binary_dijkstra(&adj, 2, 4), Some(vec![2, 3, 4]));
+        assert_eq!(binary_dijkstra(&adj, 4, 0), None);
+        // Two components, 0 <-> 1 and 2 <-> 3.
+        let adj = adjacency(4, &[(0, 1), (1, 0), (2, 3), (3, 2)]);
+        assert_eq!(binary_dijkstra(&adj, 0, 3), None);
+        assert_eq!(binary_dijkstra(&adj, 3, 2), Some(vec![3, 2]));
+        // Two shortest paths 0 -> 1 -> 3 and 0 -> 2 -> 3, and a longer one through 4.
+        let adj = adjacency(5, &[(0, 4), (4, 1), (0, 1), (0, 2), (1, 3), (2, 3)]);
+        let path = binary_dijkstra(&adj, 0, 3).unwrap();
+        assert_eq!(path.len(), 3);
+        assert!(path == vec![0, 1, 3] || path == vec![0, 2, 3]);
+        // Self-loops and edges back to the origin don't lead anywhere.
+        let adj = adjacency(3, &[(0, 0), (0, 1), (1, 1), (1, 0), (1, 2)]);
+        assert_eq!(binary_dijkstra(&adj, 0, 2), Some(vec![0, 1, 2]));
+        assert_eq!(binary_dijkstra(&adj, 0, 0), Some(vec![0]));
+    }
 }
@@ -0,0 +1,127 @@
+use super::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// A bipartite graph of articles and the categories they are in. Every edge connects an
+/// article to one of its categories, see Article::categories.
+///
+/// Projecting it onto one side connects the nodes of that side that have a neighbour in
+/// common, e.g. two articles that share a category.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CategoryGraph {
+    categories: HashMap<URL, HashSet<String>>,
+}
+
+impl CategoryGraph {
+    /// Creates a new bipartite graph from the categories of the given articles. If an
+    /// article occurs more than once, the categories of all its occurrences are combined.
+    pub fn new(articles: &[Article]) -> Self {
+        let mut categories: HashMap<URL, HashSet<String>> = HashMap::new();
+        for a in articles {
+            categories
+                .entry(a.url.clone())
+                .or_default()
+                .extend(a.categories.iter().cloned());
+        }
+        CategoryGraph { categories }
+    }
+
+    /// Returns all the articles in ascending order.
+    pub fn articles(&self) -> Vec<&URL> {
+        let mut arts: Vec<_> = self.categories.keys().collect();
+        arts.sort();
+        arts
+    }
+
+    /// Returns all the categories that at least one of the articles is in, in ascending order.
+    pub fn categories(&self) -> Vec<&String> {
+        let mut cs: Vec<_> = self.categories.values().flatten().collect();
+        cs.sort();
+        cs.dedup();
+        cs
+    }
+
+    /// Returns the members of every category.
+    pub fn members(&self) -> HashMap<&String, Vec<&URL>> {
+        let mut ms: HashMap<_, Vec<_>> = HashMap::new();
+        for (u, cs) in self.categories.iter() {
+            for c in cs {
+                ms.entry(c).or_default().push(u);
+            }
+        }
+        ms
+    }
+
+    /// Projects the graph onto the articles: two articles are connected if they share at
+    /// least one category. Every pair is only listed once, the smaller article first,
+    /// together with the number of categories they share. Pairs are in ascending order.
+    pub fn project_articles(&self) -> Vec<(URL, URL, usize)> {
+        project(self.members().into_values())
+    }
+
+    /// Projects the graph onto the categories: two categories are connected if at least
+    /// one article is in both. Every pair is only listed once, the smaller category first,
+    /// together with the number of articles they share. Pairs are in ascending order.
+    pub fn project_categories(&self) -> Vec<(String, String, usize)> {
+        project(self.categories.values().map(|cs| cs.iter().collect()))
+    }
+}
+
+/// Counts how often each pair of nodes occurs together in one of the given groups.
+fn project<'a, T: Ord + Clone + 'a>(
+    groups: impl Iterator<Item = Vec<&'a T>>,
+) -> Vec<(T, T, usize)> {
+    let mut pairs: BTreeMap<(&T, &T), usize> = BTreeMap::new();
+    for mut g in groups {
+        g.sort();
+        for (i, x) in g.iter().enumerate() {
+            for y in g[i + 1..].iter() {
+                *pairs.entry((*x, *y)).or_default() += 1;
+            }
+        }
+    }
+    pairs
+        .into_iter()
+        .map(|((x, y), n)| (x.clone(), y.clone(), n))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::*;
+
+    #[test]
+    fn projection_connects_co_members() -> Result<(), Box<dyn std::error::Error>> {
+        let site = content("<a href=\"/wiki/Oak\">oak</a>\n")
+            + "<a href=\"/wiki/Category:Trees\" title=\"Category:Trees\">Trees</a>\n\
+            <a href=\"/wiki/Category:Deciduous_plants\">Deciduous plants</a>";
+        let oak = Article::parse(url("Oak"), site)?;
+        assert_eq!(oak.references.len(), 1);
+        assert_eq!(oak.categories.len(), 2);
+        let mut arts = articles(&[("Beech", &[]), ("Pine", &[]), ("Rose", &[])]);
+        for (a, cs) in arts.iter_mut().zip(&[
+            &["Trees", "Deciduous plants"][..],
+            &["Trees"][..],
+            &["Flowers"][..],
+        ]) {
+            a.categories.extend(cs.iter().map(|c| String::from(*c)));
+        }
+        arts.push(oak);
+        let g = CategoryGraph::new(&arts);
+        assert_eq!(g.articles().len(), 4);
+        assert_eq!(g.categories(), ["Deciduous plants", "Flowers", "Trees"]);
+        assert_eq!(
+            g.project_articles(),
+            vec![
+                (url("Beech"), url("Oak"), 2),
+                (url("Beech"), url("Pine"), 1),
+                (url("Oak"), url("Pine"), 1),
+            ]
+        );
+        assert_eq!(
+            g.project_categories(),
+            vec![(String::from("Deciduous plants"), String::from("Trees"), 2)]
+        );
+        Ok(())
+    }
+}
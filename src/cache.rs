@@ -0,0 +1,242 @@
+use super::*;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use thiserror::Error;
+
+/// CacheErr is an enum that contains possible error values that could occur while
+/// reading a checkpoint.
+#[derive(Error, Debug)]
+pub enum CacheErr {
+    #[error("Invalid checkpoint file.")]
+    InvalidCheckpoint,
+}
+
+/// Converts an article into the JSON object that is stored in a cache file.
+///
+/// References are sorted, so the same article always gives the same line. The recorded
+/// dropped links are not stored.
+pub fn article_to_json(a: &Article) -> Value {
+    let mut refs: Vec<_> = a.references.iter().map(|r| r.to_string()).collect();
+    refs.sort();
+    let mut categories: Vec<_> = a.categories.iter().collect();
+    categories.sort();
+    json!({
+        "url": a.url.to_string(),
+        "references": refs,
+        "categories": categories,
+        "content": a.content,
+        "html": a.html,
+    })
+}
+
+/// Converts a JSON object as written by article_to_json back into an article. The URLs
+/// are taken as they are, so they are not normalized again.
+pub fn article_from_json(v: &Value) -> Option<Article> {
+    let url = url_from_json;
+    let mut a = Article::new(url(&v["url"])?);
+    for r in v["references"].as_array()? {
+        a.references.insert(url(r)?);
+    }
+    // Older cache files have no categories.
+    if let Some(cs) = v["categories"].as_array() {
+        for c in cs {
+            a.categories.insert(String::from(c.as_str()?));
+        }
+    }
+    a.content = v["content"].as_str().map(String::from);
+    a.html = v["html"].as_str().map(String::from);
+    Some(a)
+}
+
+/// Converts a full URL as written by URL::to_string back into a URL on the same domain.
+fn url_from_json(v: &Value) -> Option<URL> {
+    let s = v.as_str()?;
+    let domain = DomainConfig::for_url(s).unwrap_or_else(DomainConfig::current);
+    URL::new_in(s, domain, CaseSensitivity::Sensitive).ok()
+}
+
+/// Appends the given articles to a cache file, one JSON object per line. The file is
+/// created if it does not exist yet.
+///
+/// Nothing that is already in the file is rewritten, so if an article is written more
+/// than once, the last line for it is the one that counts (see read_articles). If a
+/// write is interrupted, only the last line is cut off, which read_articles skips.
+pub fn append_articles<P: AsRef<Path>>(
+    path: P,
+    articles: &[&Article],
+) -> Result<(), Box<dyn Error>> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)?;
+    let mut out = String::new();
+    if file.metadata()?.len() > 0 {
+        let mut last = [0];
+        file.seek(SeekFrom::End(-1))?;
+        file.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            // The last line is incomplete, so at least it shouldn't spoil the next one.
+            out.push('\n');
+        }
+    }
+    for a in articles {
+        out.push_str(&article_to_json(a).to_string());
+        out.push('\n');
+    }
+    file.write_all(out.as_bytes())?;
+    Ok(())
+}
+
+/// Reads all the articles from a cache file. If an article occurs more than once, only
+/// its last occurrence is returned. Empty lines are skipped, and so are lines that are
+/// not valid articles, e.g. because the file was cut off. The number of those invalid
+/// lines is returned alongside the articles.
+pub fn read_articles<P: AsRef<Path>>(path: P) -> Result<(Vec<Article>, usize), Box<dyn Error>> {
+    let contents = fs::read_to_string(path)?;
+    let mut arts: Vec<Article> = Vec::new();
+    let mut index = HashMap::new();
+    let mut invalid = 0;
+    for line in contents.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let a = match serde_json::from_str(line)
+            .ok()
+            .and_then(|v| article_from_json(&v))
+        {
+            Some(a) => a,
+            None => {
+                invalid += 1;
+                continue;
+            }
+        };
+        match index.get(&a.url) {
+            Some(j) => arts[*j] = a,
+            None => {
+                index.insert(a.url.clone(), arts.len());
+                arts.push(a);
+            }
+        }
+    }
+    Ok((arts, invalid))
+}
+
+/// Replaces the file at the given path with the given contents by writing them to a
+/// temporary file next to it first, which is then renamed. If this is interrupted, the
+/// old file is still intact.
+fn write_atomically(path: &Path, contents: &str) -> Result<(), Box<dyn Error>> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// The state of a neighbourhood crawl that was interrupted, so it can be resumed later.
+/// See CollectorBuilder::time_limit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// The URLs the neighbourhood is built around.
+    pub seeds: Vec<URL>,
+    /// The number of levels that still have to be expanded.
+    pub depth: u32,
+    /// The URLs of the next level, which have not been expanded yet.
+    pub frontier: Vec<URL>,
+    /// All the URLs that have been encountered so far.
+    pub seen: Vec<URL>,
+    /// The articles that have been collected so far.
+    pub articles: Vec<Article>,
+}
+
+impl Checkpoint {
+    /// Writes the checkpoint to a file as a single JSON object, replacing the file if it
+    /// exists already. The URLs are sorted, so the same state always gives the same file.
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let urls = |us: &[URL]| {
+            let mut v: Vec<_> = us.iter().map(|u| u.to_string()).collect();
+            v.sort();
+            v
+        };
+        let mut arts: Vec<_> = self.articles.iter().collect();
+        arts.sort_by(|a, b| a.url.cmp(&b.url));
+        let v = json!({
+            "seeds": self.seeds.iter().map(|u| u.to_string()).collect::<Vec<_>>(),
+            "depth": self.depth,
+            "frontier": urls(&self.frontier),
+            "seen": urls(&self.seen),
+            "articles": arts.into_iter().map(article_to_json).collect::<Vec<_>>(),
+        });
+        write_atomically(Path::new(path), &v.to_string())
+    }
+
+    /// Reads a checkpoint that was written by Checkpoint::save.
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let v: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+        Checkpoint::from_json(&v)
+            .ok_or_else(|| Box::new(CacheErr::InvalidCheckpoint) as Box<dyn Error>)
+    }
+
+    fn from_json(v: &Value) -> Option<Self> {
+        let urls =
+            |v: &Value| -> Option<Vec<URL>> { v.as_array()?.iter().map(url_from_json).collect() };
+        Some(Checkpoint {
+            seeds: urls(&v["seeds"])?,
+            depth: v["depth"].as_u64()? as u32,
+            frontier: urls(&v["frontier"])?,
+            seen: urls(&v["seen"])?,
+            articles: v["articles"]
+                .as_array()?
+                .iter()
+                .map(article_from_json)
+                .collect::<Option<_>>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::*;
+
+    #[test]
+    fn articles_survive_round_trip() -> Result<(), Box<dyn Error>> {
+        let mut arts = articles(&[("Tree", &["Plant", "Leaf"]), ("Plant", &[])]);
+        arts[1].content = Some(String::from("A plant is a living thing."));
+        arts[1].categories.insert(String::from("Plants"));
+        let path = std::env::temp_dir().join(format!("wikigraph-{}-cache", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = fs::remove_file(path);
+        append_articles(path, &[&arts[0]])?;
+        append_articles(path, &[&arts[0], &arts[1]])?;
+        let (read, invalid) = read_articles(path)?;
+        assert_eq!(invalid, 0);
+        fs::write(path, "{\"url\": \"/wiki/Tree\"}\n")?;
+        assert_eq!(read_articles(path)?, (vec![], 1));
+        fs::remove_file(path)?;
+        assert_eq!(read, arts);
+        Ok(())
+    }
+
+    #[test]
+    fn cut_off_lines_are_skipped() -> Result<(), Box<dyn Error>> {
+        let arts = articles(&[("Tree", &["Plant"]), ("Plant", &["Tree"])]);
+        let path = std::env::temp_dir().join(format!("wikigraph-{}-cut", std::process::id()));
+        let line = article_to_json(&arts[0]).to_string();
+        // A run that was interrupted while writing the second line.
+        fs::write(&path, format!("{}\n{}", line, &line[..20]))?;
+        append_articles(&path, &[&arts[1]])?;
+        let (read, invalid) = read_articles(&path)?;
+        fs::remove_file(&path)?;
+        assert_eq!(read, arts);
+        assert_eq!(invalid, 1);
+        let mut tmp = path.into_os_string();
+        tmp.push(".tmp");
+        assert!(fs::metadata(tmp).is_err());
+        Ok(())
+    }
+}
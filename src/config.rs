@@ -1,6 +1,10 @@
-use super::url::URL;
+use super::url::{CaseSensitivity, URL};
+use std::collections::HashSet;
 use std::error::Error;
 use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
 use thiserror::Error;
 /// Contains the prefix that is used to identify Wikipedia articles.
 ///
@@ -10,6 +14,9 @@ pub const WIKI_ARTICLE_PREFIX: &str = "/wiki/";
 
 pub const WIKI_DOMAIN: &str = "https://en.wikipedia.org";
 
+/// Contains the path of the MediaWiki API relative to `WIKI_DOMAIN`.
+pub const WIKI_API_PATH: &str = "/w/api.php";
+
 /// Contains prefixes of webpages that are not considered Wikipedia articles.
 ///
 /// Any url of the form "<WIKI_ARTICLE_PREFIX><BLACKLIST_ELEMENT><REST>",
@@ -26,9 +33,260 @@ pub const WIKI_ARTICLE_PREFIX_BLACKLIST: [&str; 8] = [
     "Portal:",
 ];
 
+/// Contains the namespaces of the English Wikipedia (and their aliases) apart from the
+/// articles themselves, with underscores instead of spaces.
+///
+/// A title that starts with one of these and a colon is not an article, e.g.
+/// `Template:Tree`, whereas other titles may contain colons, e.g. `John_3:16`.
+pub const WIKI_NAMESPACES: [&str; 32] = [
+    "Talk",
+    "User",
+    "User_talk",
+    "Wikipedia",
+    "Wikipedia_talk",
+    "WP",
+    "WT",
+    "Project",
+    "Project_talk",
+    "File",
+    "File_talk",
+    "Image",
+    "MediaWiki",
+    "MediaWiki_talk",
+    "Template",
+    "Template_talk",
+    "Help",
+    "Help_talk",
+    "Category",
+    "Category_talk",
+    "Portal",
+    "Portal_talk",
+    "Draft",
+    "Draft_talk",
+    "TimedText",
+    "TimedText_talk",
+    "Module",
+    "Module_talk",
+    "Special",
+    "Media",
+    "Event",
+    "Event_talk",
+];
+
+/// Contains the prefix of links to the categories of an article, which are collected
+/// separately from the references.
+pub const WIKI_CATEGORY_PREFIX: &str = "/wiki/Category:";
+
 pub const WIKI_ARTICLE_SUFFIX_BLACKLIST: [&str; 1] = ["_(disambiguation)"];
 
+/// Contains the names of the categories that disambiguation pages are in.
+///
+/// Not all of these pages have the `_(disambiguation)` suffix, e.g. `/wiki/Mercury` is one,
+/// so they are recognised by their categories after they have been fetched.
+pub const WIKI_DISAMBIGUATION_CATEGORIES: [&str; 2] =
+    ["All disambiguation pages", "Disambiguation pages"];
+
+/// Contains the headings of the sections that list the sources of an article. Everything
+/// after them, e.g. further reading and external links, is tangential to the topic.
+pub const WIKI_REFERENCE_HEADINGS: [&str; 1] = ["References"];
+
 pub const REFERENCE_PREFIX: &str = "<a href=\"";
+
+/// Contains the start of the link to the canonical URL of an article in its HTML. When a
+/// redirect is requested, e.g. `/wiki/USA`, this points to the article that is served
+/// instead, e.g. `/wiki/United_States`.
+pub const CANONICAL_PREFIX: &str = "<link rel=\"canonical\" href=\"";
+
+/// Contains the CSS selector of the element that holds the actual content of an article,
+/// as opposed to the navigation, the sidebar and the footer around it.
+pub const WIKI_CONTENT_SELECTOR: &str = "#mw-content-text";
+
+/// The User-Agent header of the requests a Collector sends unless configured otherwise.
+/// Wikimedia asks for a user agent that names the tool and a way to contact its operator,
+/// so anyone who crawls a lot should set their own, see CollectorBuilder::user_agent.
+pub const DEFAULT_USER_AGENT: &str = concat!(
+    "wikigraph/",
+    env!("CARGO_PKG_VERSION"),
+    " (https://github.com/MiltFra/wikigraph)"
+);
+
+/// How long a Collector waits for the response to a request before giving up on it
+/// unless configured otherwise.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The number of requests a Collector sends at once unless configured otherwise.
+pub const DEFAULT_CONCURRENCY: usize = 8;
+
+/// The number of times a Collector repeats a request that failed with a transient error
+/// unless configured otherwise.
+pub const DEFAULT_RETRIES: usize = 3;
+
+/// The time a Collector waits before repeating a failed request for the first time unless
+/// configured otherwise. It is doubled for every further repetition.
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Contains markers of the pages that are served instead of an article when too many
+/// requests are sent, e.g. a redirect to the login page. A page that contains any of
+/// these is not parsed as an article.
+pub const RATE_LIMIT_MARKERS: [&str; 3] = [
+    "\"wgCanonicalSpecialPageName\":\"Userlogin\"",
+    "<title>Log in",
+    "<title>Too many requests",
+];
+
+/// The namespaces and suffixes that mark pages which are not considered articles on
+/// a particular Wikipedia domain, e.g. in a particular language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DomainConfig {
+    /// The domain including the scheme, e.g. `https://en.wikipedia.org`.
+    pub domain: &'static str,
+    /// See `WIKI_ARTICLE_PREFIX_BLACKLIST`.
+    pub prefix_blacklist: &'static [&'static str],
+    /// See `WIKI_ARTICLE_SUFFIX_BLACKLIST`.
+    pub suffix_blacklist: &'static [&'static str],
+    /// See `WIKI_NAMESPACES`.
+    pub namespaces: &'static [&'static str],
+    /// See `WIKI_CATEGORY_PREFIX`.
+    pub category_prefix: &'static str,
+    /// The categories that mark disambiguation pages, see `WIKI_DISAMBIGUATION_CATEGORIES`.
+    pub disambiguation_categories: &'static [&'static str],
+    /// See `WIKI_REFERENCE_HEADINGS`.
+    pub reference_headings: &'static [&'static str],
+}
+
+/// Contains the configurations of all the known Wikipedia domains.
+pub const DOMAIN_CONFIGS: [DomainConfig; 3] = [
+    DomainConfig {
+        domain: WIKI_DOMAIN,
+        prefix_blacklist: &WIKI_ARTICLE_PREFIX_BLACKLIST,
+        suffix_blacklist: &WIKI_ARTICLE_SUFFIX_BLACKLIST,
+        namespaces: &WIKI_NAMESPACES,
+        category_prefix: WIKI_CATEGORY_PREFIX,
+        disambiguation_categories: &WIKI_DISAMBIGUATION_CATEGORIES,
+        reference_headings: &WIKI_REFERENCE_HEADINGS,
+    },
+    DomainConfig {
+        domain: "https://de.wikipedia.org",
+        prefix_blacklist: &[
+            "Kategorie:",
+            "Diskussion:",
+            "Hilfe:",
+            "Wikipedia:",
+            "Spezial:",
+            "Datei:",
+            "Portal:",
+            "Benutzer:",
+            "Vorlage:",
+        ],
+        suffix_blacklist: &["_(Begriffsklärung)"],
+        namespaces: &[
+            "Diskussion",
+            "Benutzer",
+            "Benutzer_Diskussion",
+            "Benutzerin",
+            "Benutzerin_Diskussion",
+            "Wikipedia",
+            "Wikipedia_Diskussion",
+            "WP",
+            "Datei",
+            "Datei_Diskussion",
+            "Bild",
+            "MediaWiki",
+            "MediaWiki_Diskussion",
+            "Vorlage",
+            "Vorlage_Diskussion",
+            "Hilfe",
+            "Hilfe_Diskussion",
+            "Kategorie",
+            "Kategorie_Diskussion",
+            "Portal",
+            "Portal_Diskussion",
+            "Modul",
+            "Modul_Diskussion",
+            "Spezial",
+            "Medium",
+        ],
+        category_prefix: "/wiki/Kategorie:",
+        disambiguation_categories: &["Begriffsklärung"],
+        reference_headings: &["Einzelnachweise"],
+    },
+    DomainConfig {
+        domain: "https://fr.wikipedia.org",
+        prefix_blacklist: &[
+            "Catégorie:",
+            "Discussion:",
+            "Aide:",
+            "Wikipédia:",
+            "Spécial:",
+            "Fichier:",
+            "Portail:",
+            "Utilisateur:",
+            "Modèle:",
+        ],
+        suffix_blacklist: &["_(homonymie)"],
+        namespaces: &[
+            "Discussion",
+            "Utilisateur",
+            "Discussion_utilisateur",
+            "Wikipédia",
+            "Discussion_Wikipédia",
+            "WP",
+            "Fichier",
+            "Discussion_fichier",
+            "MediaWiki",
+            "Discussion_MediaWiki",
+            "Modèle",
+            "Discussion_modèle",
+            "Aide",
+            "Discussion_aide",
+            "Catégorie",
+            "Discussion_catégorie",
+            "Portail",
+            "Discussion_Portail",
+            "Projet",
+            "Discussion_Projet",
+            "Module",
+            "Discussion_module",
+            "Spécial",
+            "Média",
+        ],
+        category_prefix: "/wiki/Catégorie:",
+        disambiguation_categories: &["Homonymie"],
+        reference_headings: &["Notes et références", "Références"],
+    },
+];
+
+impl DomainConfig {
+    /// Returns the configuration of the given domain, if it is known.
+    pub fn for_domain(domain: &str) -> Option<&'static DomainConfig> {
+        DOMAIN_CONFIGS.iter().find(|c| c.domain == domain)
+    }
+
+    /// Returns the configuration of the given domain, which may be given without the
+    /// scheme, e.g. `de.wikipedia.org`.
+    pub fn parse(domain: &str) -> Result<&'static DomainConfig, ConfigErr> {
+        let full = if domain.contains("://") {
+            String::from(domain)
+        } else {
+            format!("https://{}", domain)
+        };
+        DomainConfig::for_domain(full.trim_end_matches('/'))
+            .ok_or_else(|| ConfigErr::UnknownDomain(String::from(domain)))
+    }
+
+    /// Returns the configuration of the domain the given absolute URL is on, if it is known.
+    pub fn for_url(url: &str) -> Option<&'static DomainConfig> {
+        DOMAIN_CONFIGS.iter().find(|c| {
+            url.strip_prefix(c.domain)
+                .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+
+    /// Returns the configuration of `WIKI_DOMAIN`, the default domain.
+    pub fn current() -> &'static DomainConfig {
+        &DOMAIN_CONFIGS[0]
+    }
+}
 /// ConfigErr is an enum that contains possible error values that
 /// could occur during the Configuration of this library in Config::new.
 #[derive(Error, Debug)]
@@ -47,63 +305,392 @@ pub enum ConfigErr {
     /// starting point is required.
     #[error("Found no valid urls in the file.")]
     NoValidUrls,
+    /// This error is returned when there is no file at the given path.
+    #[error("Could not find the file {0}.")]
+    InputFileNotFound(String),
+    /// This error is returned when the file exists, but may not be read.
+    #[error("Not allowed to read the file {0}.")]
+    InputFilePermissionDenied(String),
+    /// This error is returned when the given domain is not one of `DOMAIN_CONFIGS`.
+    #[error("Unknown Wikipedia domain {0}.")]
+    UnknownDomain(String),
+    /// This error is returned when the file contains a URL on another known domain than
+    /// the configured one, i.e. `(configured domain, URL)`.
+    #[error("Expected only articles on {0}, found {1}.")]
+    MixedDomains(String, String),
+    /// This error is returned when the given output format is not one of OutputFormat.
+    #[error("Unknown output format {0}. (expected: debug, json or plain)")]
+    UnknownFormat(String),
+}
+
+/// OutputFormat determines how `run` prints the paths it has found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Every path is printed as the debug representation of the list of names.
+    #[default]
+    Debug,
+    /// All the paths are printed as a single JSON array, see `export_paths_json`.
+    Json,
+    /// Every path is printed on its own line, e.g. `Tree -> Plant -> Sun`.
+    Plain,
+}
+
+impl OutputFormat {
+    /// Returns the format with the given name, i.e. `debug`, `json` or `plain`.
+    pub fn parse(name: &str) -> Result<Self, ConfigErr> {
+        match name {
+            "debug" => Ok(OutputFormat::Debug),
+            "json" => Ok(OutputFormat::Json),
+            "plain" => Ok(OutputFormat::Plain),
+            _ => Err(ConfigErr::UnknownFormat(String::from(name))),
+        }
+    }
 }
 /// Config is a struct used to encapsulate all the possible configurations
 /// for the wikigraph library.
 pub struct Config {
-    /// Contains a list of URLs to valid Wikipedia articles.
+    /// Contains a list of URLs to valid Wikipedia articles. Every article is only
+    /// contained once.
     pub urls: Vec<URL>,
-    ///// Contains the depth for the search in the Wikipedia graph.
-    // pub depth: u32,
+    /// The number of duplicate URLs that were removed from the input.
+    pub duplicates: usize,
+    /// The Wikipedia domain all the articles are on, including the scheme. This is
+    /// `WIKI_DOMAIN` unless another one is given through `--domain`.
+    pub domain: String,
+    /// How the paths are printed. This is OutputFormat::Debug unless another format is
+    /// given through `--format`.
+    pub output_format: OutputFormat,
+    /// The number of steps a search for a path may take before it gives up, if it is
+    /// given through `--depth`. See CollectorBuilder::max_depth. With only one URL, this is
+    /// the depth of the neighbourhood that is printed instead, see `run`.
+    pub depth: Option<u32>,
 }
 
 impl Config {
     /// Given an iterator over the command line arguments, this will return
     /// an appropriate config struct.
     ///
-    /// Excatly two arguments are expected, otherwise an error is returned.
-    /// - An integer containing the desired search depth.
-    /// - A file name containing the starting URLs.
+    /// At least one argument is expected, otherwise an error is returned.
+    /// - A file name containing the starting URLs, see Config::get_urls.
+    /// - Or the starting articles themselves, as titles or URLs, if there is more than one
+    ///   argument or the only one is not an existing file, e.g. `Tree "Solar System"`.
+    ///
+    /// It may be preceded by `--domain <DOMAIN>`, e.g. `--domain de.wikipedia.org`, to
+    /// crawl another Wikipedia than the English one, by `--format <FORMAT>`, e.g.
+    /// `--format json`, see OutputFormat::parse, and by `--depth <DEPTH>` to limit the
+    /// length of the paths that are searched for.
     pub fn new(mut args: std::env::Args) -> Result<Self, Box<dyn Error>> {
-        eprintln!("Creating config");
         // Dropping the name of the executable.
         args.next();
-        //// Parsing the depth.
-        //let n = match args.next() {
-        //    Some(arg) => match arg.parse() {
-        //        Ok(v) => v,
-        //        Err(_) => return Err(Box::new(ConfigErr::IntParseError(arg))),
-        //    },
-        //    None => return Err(Box::new(ConfigErr::TooFewArguments)),
-        //};
-        // Parsing the URL file
-        let urls = match args.next() {
-            Some(arg) => Config::get_urls(&arg),
-            None => return Err(Box::new(ConfigErr::TooFewArguments)),
+        Config::from_args(args)
+    }
+
+    /// Does the same as Config::new, but for the arguments after the name of the executable.
+    fn from_args<I: Iterator<Item = String>>(mut args: I) -> Result<Self, Box<dyn Error>> {
+        let mut domain = DomainConfig::current();
+        let mut output_format = OutputFormat::default();
+        let mut depth = None;
+        let mut positional = Vec::new();
+        while let Some(arg) = args.next() {
+            if arg == "--domain" {
+                let d = args.next().ok_or(ConfigErr::TooFewArguments)?;
+                domain = DomainConfig::parse(&d)?;
+            } else if arg == "--format" {
+                let f = args.next().ok_or(ConfigErr::TooFewArguments)?;
+                output_format = OutputFormat::parse(&f)?;
+            } else if arg == "--depth" {
+                let n = args.next().ok_or(ConfigErr::TooFewArguments)?;
+                match n.parse() {
+                    Ok(v) => depth = Some(v),
+                    Err(_) => return Err(Box::new(ConfigErr::IntParseError(n))),
+                }
+            } else {
+                positional.push(arg);
+            }
+        }
+        let urls = match &positional[..] {
+            [] => return Err(Box::new(ConfigErr::TooFewArguments)),
+            [path] if Config::is_path(path) => Config::get_urls(path, domain),
+            articles => {
+                let lines: Vec<_> = articles.iter().map(|a| Config::article_url(a)).collect();
+                Config::parse_urls(&lines, domain)
+            }
         };
         match urls {
             Err(e) => Err(e),
-            Ok(v) => Ok(Config {
-                urls: v, /*depth: n */
+            Ok((v, duplicates)) => Ok(Config {
+                urls: v,
+                duplicates,
+                domain: String::from(domain.domain),
+                output_format,
+                depth,
             }),
         }
     }
 
-    pub fn iter_urls(&self) -> std::slice::Iter<URL> {
+    /// Creates a config for the given starting URLs without reading any arguments or
+    /// files, e.g. to use the crate as a library. Duplicates are removed like in
+    /// Config::get_urls, and the domain is the one of the first URL.
+    ///
+    /// Everything else has its default value, apart from the depth. Just like `--depth`,
+    /// a depth with a single URL asks `run` for its neighbourhood instead of paths.
+    pub fn from_urls(urls: Vec<URL>, depth: Option<u32>) -> Self {
+        let domain = urls
+            .first()
+            .map_or(DomainConfig::current().domain, |u| u.domain_config().domain);
+        let n = urls.len();
+        let mut seen = HashSet::new();
+        let urls: Vec<URL> = urls
+            .into_iter()
+            .filter(|u| seen.insert(u.clone()))
+            .collect();
+        Config {
+            duplicates: n - urls.len(),
+            urls,
+            domain: String::from(domain),
+            output_format: OutputFormat::default(),
+            depth,
+        }
+    }
+
+    pub fn iter_urls(&self) -> std::slice::Iter<'_, URL> {
         self.urls.iter()
     }
 
     /// Filters all the valid Wikipedia articles from a given String.
-    /// Articles have to be on separate lines and follow the criteria specified in the scraper module.
-    fn get_urls(path: &String) -> Result<Vec<URL>, Box<dyn Error>> {
-        eprintln!("Parsing URLs");
-        let contents = fs::read_to_string(path)?;
-        let valid_urls = URL::new_list(&contents);
-        if valid_urls.len() == 0 {
+    /// Articles have to be on separate lines and be valid as described for URL::new.
+    ///
+    /// If an article occurs more than once, only the first occurrence is kept. The number of
+    /// removed duplicates is returned alongside the URLs.
+    ///
+    /// All the articles have to be on the given domain. URLs on any other known domain are
+    /// rejected with ConfigErr::MixedDomains, as there are no paths between them anyway.
+    fn get_urls(
+        path: &str,
+        domain: &'static DomainConfig,
+    ) -> Result<(Vec<URL>, usize), Box<dyn Error>> {
+        let contents = fs::read_to_string(path).map_err(|e| -> Box<dyn Error> {
+            match e.kind() {
+                io::ErrorKind::NotFound => Box::new(ConfigErr::InputFileNotFound(path.into())),
+                io::ErrorKind::PermissionDenied => {
+                    Box::new(ConfigErr::InputFilePermissionDenied(path.into()))
+                }
+                _ => Box::new(e),
+            }
+        })?;
+        Config::parse_urls(&contents.lines().collect::<Vec<_>>(), domain)
+    }
+
+    /// Whether the only argument is the file name of the starting URLs rather than an
+    /// article. Apart from existing files, anything that looks like a path is taken as
+    /// one, i.e. if it contains a slash or a backslash or ends with a file extension, so
+    /// a mistyped file name is reported instead of being crawled.
+    ///
+    /// Titles like `Node.js` can still be given as URLs, e.g. `/wiki/Node.js`.
+    fn is_path(arg: &str) -> bool {
+        if arg.starts_with(WIKI_ARTICLE_PREFIX) || arg.contains("://") {
+            return false;
+        }
+        let path = Path::new(arg);
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        path.is_file()
+            || arg.contains(['/', '\\'])
+            || (1..=4).contains(&extension.len())
+                && extension.chars().all(|c| c.is_ascii_alphanumeric())
+    }
+
+    /// Turns an article that was given as an argument into a URL that can be parsed,
+    /// i.e. titles are prefixed with `WIKI_ARTICLE_PREFIX` and URLs are kept.
+    fn article_url(arg: &str) -> String {
+        if arg.starts_with('/') || arg.contains("://") {
+            String::from(arg)
+        } else {
+            format!("{}{}", WIKI_ARTICLE_PREFIX, arg)
+        }
+    }
+
+    /// Does the same as Config::get_urls, but for lines that were read already.
+    fn parse_urls(
+        lines: &[impl AsRef<str>],
+        domain: &'static DomainConfig,
+    ) -> Result<(Vec<URL>, usize), Box<dyn Error>> {
+        let lines = lines.iter().map(|l| l.as_ref());
+        if let Some(other) = lines
+            .clone()
+            .find(|l| DomainConfig::for_url(l).is_some_and(|c| c != domain))
+        {
+            return Err(Box::new(ConfigErr::MixedDomains(
+                String::from(domain.domain),
+                String::from(other),
+            )));
+        }
+        let mut seen = HashSet::new();
+        let mut valid_urls: Vec<URL> = lines
+            .filter_map(|x| URL::new_in(x, domain, CaseSensitivity::default()).ok())
+            .collect();
+        let n = valid_urls.len();
+        valid_urls.retain(|u| seen.insert(u.clone()));
+        if valid_urls.is_empty() {
             return Err(Box::new(ConfigErr::NoValidUrls));
         }
-        return Ok(valid_urls);
+        let duplicates = n - valid_urls.len();
+        Ok((valid_urls, duplicates))
     }
 }
 
-// TODO: Write tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_file(name: &str, contents: &str) -> PathBuf {
+        let p = std::env::temp_dir().join(format!("wikigraph-{}-{}", std::process::id(), name));
+        fs::write(&p, contents).unwrap();
+        p
+    }
+
+    #[test]
+    fn get_urls_removes_duplicates() -> Result<(), Box<dyn Error>> {
+        let p = temp_file(
+            "duplicates",
+            "/wiki/Tree\nYouTube\nhttps://en.wikipedia.org/wiki/Tree\n/wiki/Astronomy\n/wiki/tree\n/wiki/Astronomy",
+        );
+        let (urls, duplicates) = Config::get_urls(p.to_str().unwrap(), DomainConfig::current())?;
+        fs::remove_file(p)?;
+        let names: Vec<_> = urls.iter().map(|u| u.get_name()).collect();
+        assert_eq!(names, vec!["Tree", "Astronomy"]);
+        assert_eq!(duplicates, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn get_urls_reports_missing_file() {
+        let p = std::env::temp_dir().join(format!("wikigraph-{}-missing", std::process::id()));
+        let p = p.to_str().unwrap();
+        let e = Config::get_urls(p, DomainConfig::current()).unwrap_err();
+        match e.downcast_ref::<ConfigErr>() {
+            Some(ConfigErr::InputFileNotFound(f)) => assert_eq!(f, p),
+            _ => panic!("unexpected error: {}", e),
+        }
+        let p = temp_file("invalid", "YouTube\n/wiki/Help:Contents");
+        let e = Config::get_urls(p.to_str().unwrap(), DomainConfig::current()).unwrap_err();
+        fs::remove_file(p).unwrap();
+        assert!(matches!(e.downcast_ref(), Some(ConfigErr::NoValidUrls)));
+    }
+
+    #[test]
+    fn domain_is_configurable() -> Result<(), Box<dyn Error>> {
+        let p = temp_file(
+            "domain",
+            "https://de.wikipedia.org/wiki/Größe\n/wiki/Baum\n/wiki/Kategorie:Baum",
+        );
+        let path = String::from(p.to_str().unwrap());
+        let args = |a: &[&str]| a.iter().map(|s| String::from(*s)).collect::<Vec<_>>();
+        let cfg = Config::from_args(args(&["--domain", "de.wikipedia.org", &path]).into_iter())?;
+        assert_eq!(cfg.domain, "https://de.wikipedia.org");
+        let urls: Vec<_> = cfg.urls.iter().map(|u| u.to_string()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://de.wikipedia.org/wiki/Größe",
+                "https://de.wikipedia.org/wiki/Baum"
+            ]
+        );
+        let e = Config::from_args(args(&[&path]).into_iter()).err().unwrap();
+        fs::remove_file(p)?;
+        match e.downcast_ref::<ConfigErr>() {
+            Some(ConfigErr::MixedDomains(d, u)) => {
+                assert_eq!(d, WIKI_DOMAIN);
+                assert_eq!(u, "https://de.wikipedia.org/wiki/Größe");
+            }
+            _ => panic!("unexpected error: {}", e),
+        }
+        let e = Config::from_args(args(&["--domain", "xx.wikipedia.org", &path]).into_iter())
+            .err()
+            .unwrap();
+        assert!(matches!(
+            e.downcast_ref(),
+            Some(ConfigErr::UnknownDomain(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn articles_can_be_given_directly() -> Result<(), Box<dyn Error>> {
+        let args = |a: &[&str]| a.iter().map(|s| String::from(*s)).collect::<Vec<_>>();
+        let cfg = Config::from_args(
+            args(&[
+                "--depth",
+                "2",
+                "Tree",
+                "Solar System",
+                "https://en.wikipedia.org/wiki/Tree",
+                "/wiki/Astronomy",
+            ])
+            .into_iter(),
+        )?;
+        let names: Vec<_> = cfg.urls.iter().map(|u| u.get_name()).collect();
+        assert_eq!(names, ["Tree", "Solar System", "Astronomy"]);
+        assert_eq!(cfg.duplicates, 1);
+        assert_eq!(cfg.depth, Some(2));
+        // A single argument that looks like a path is a file, even if it doesn't exist.
+        let missing = std::env::temp_dir().join(format!("wikigraph-{}-none", std::process::id()));
+        for path in [missing.to_str().unwrap(), "urls.tx", "input\\urls"].iter() {
+            let e = Config::from_args(args(&[path]).into_iter()).err().unwrap();
+            match e.downcast_ref::<ConfigErr>() {
+                Some(ConfigErr::InputFileNotFound(f)) => assert_eq!(f, path),
+                _ => panic!("unexpected error: {}", e),
+            }
+        }
+        let cfg = Config::from_args(args(&["Tree"]).into_iter())?;
+        assert_eq!(cfg.urls, [URL::new("/wiki/Tree")?]);
+        let cfg = Config::from_args(args(&["/wiki/Node.js"]).into_iter())?;
+        assert_eq!(cfg.urls, [URL::new("/wiki/Node.js")?]);
+        let e = Config::from_args(args(&["Help:Contents"]).into_iter())
+            .err()
+            .unwrap();
+        assert!(matches!(e.downcast_ref(), Some(ConfigErr::NoValidUrls)));
+
+        let cfg = Config::from_urls(
+            vec![URL::new("/wiki/tree")?, URL::new("/wiki/Tree")?],
+            Some(1),
+        );
+        assert_eq!(cfg.urls, [URL::new("/wiki/Tree")?]);
+        assert_eq!(cfg.duplicates, 1);
+        assert_eq!(cfg.domain, WIKI_DOMAIN);
+        assert_eq!(cfg.depth, Some(1));
+        assert_eq!(Config::from_urls(cfg.urls, None).depth, None);
+        assert_eq!(cfg.output_format, OutputFormat::Debug);
+        Ok(())
+    }
+
+    #[test]
+    fn output_format_and_depth_are_configurable() -> Result<(), Box<dyn Error>> {
+        let p = temp_file("format", "/wiki/Tree\n");
+        let path = String::from(p.to_str().unwrap());
+        let args = |a: &[&str]| a.iter().map(|s| String::from(*s)).collect::<Vec<_>>();
+        let cfg = Config::from_args(args(&[&path]).into_iter())?;
+        assert_eq!(cfg.output_format, OutputFormat::Debug);
+        let cfg = Config::from_args(args(&["--format", "json", &path]).into_iter())?;
+        assert_eq!(cfg.output_format, OutputFormat::Json);
+        assert_eq!(cfg.depth, None);
+        let cfg = Config::from_args(args(&["--depth", "3", &path]).into_iter())?;
+        assert_eq!(cfg.depth, Some(3));
+        let e = Config::from_args(args(&["--depth", "far", &path]).into_iter())
+            .err()
+            .unwrap();
+        assert!(matches!(
+            e.downcast_ref(),
+            Some(ConfigErr::IntParseError(_))
+        ));
+        let e = Config::from_args(args(&["--format", "yaml", &path]).into_iter())
+            .err()
+            .unwrap();
+        fs::remove_file(p)?;
+        assert!(matches!(
+            e.downcast_ref(),
+            Some(ConfigErr::UnknownFormat(_))
+        ));
+        Ok(())
+    }
+}
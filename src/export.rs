@@ -0,0 +1,600 @@
+use super::*;
+use serde_json::json;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Exports a set of articles as a Pajek network (`.net`), as it is read by Pajek and
+/// a lot of other network analysis tools.
+///
+/// Vertices are labelled with the names of the articles and numbered from 1 in ascending
+/// order of their URLs. Only references between the given articles become arcs. Pajek
+/// does not allow escaping quotes in labels, so they are replaced by single quotes.
+///
+/// # Examples
+///
+/// ```
+/// use wglib::{export, Article, URL};
+///
+/// let mut a = Article::new(URL::new("/wiki/Tree").unwrap());
+/// a.references.insert(URL::new("/wiki/Plant").unwrap());
+/// let b = Article::new(URL::new("/wiki/Plant").unwrap());
+///
+/// assert_eq!(
+///     export::export_pajek(&[a, b]),
+///     "*Vertices 2\n1 \"Plant\"\n2 \"Tree\"\n*Arcs\n2 1\n"
+/// );
+/// ```
+pub fn export_pajek(articles: &[Article]) -> String {
+    let g = Graph::new(articles);
+    let nodes = g.nodes();
+    let ids: HashMap<&URL, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i + 1)).collect();
+    let mut out = format!("*Vertices {}\n", nodes.len());
+    for n in nodes.iter() {
+        out.push_str(&format!(
+            "{} \"{}\"\n",
+            ids[n],
+            n.get_name().replace('"', "'")
+        ));
+    }
+    out.push_str("*Arcs\n");
+    for n in nodes.iter() {
+        for r in g.neighbours(n) {
+            out.push_str(&format!("{} {}\n", ids[n], ids[r]));
+        }
+    }
+    out
+}
+
+/// Exports a set of articles as a directed graph in the DOT language of Graphviz, e.g. to
+/// render it with `dot -Tpng`.
+///
+/// Nodes are labelled with the names of the articles and listed in ascending order of
+/// their URLs, followed by the edges. Only references between the given articles become
+/// edges. Quotes and backslashes in the names are escaped.
+///
+/// # Examples
+///
+/// ```
+/// use wglib::{export, Article, URL};
+///
+/// let mut a = Article::new(URL::new("/wiki/Tree").unwrap());
+/// a.references.insert(URL::new("/wiki/Plant").unwrap());
+/// let b = Article::new(URL::new("/wiki/Plant").unwrap());
+///
+/// assert_eq!(
+///     export::export_dot(&[a, b]),
+///     "digraph {\n    \"Plant\";\n    \"Tree\";\n    \"Tree\" -> \"Plant\";\n}\n"
+/// );
+/// ```
+pub fn export_dot(articles: &[Article]) -> String {
+    let g = Graph::new(articles);
+    let quote = |u: &URL| {
+        let name = u.get_name().replace('\\', "\\\\").replace('"', "\\\"");
+        format!("\"{}\"", name)
+    };
+    let mut out = String::from("digraph {\n");
+    for n in g.nodes() {
+        out.push_str(&format!("    {};\n", quote(n)));
+    }
+    for n in g.nodes() {
+        for r in g.neighbours(n) {
+            out.push_str(&format!("    {} -> {};\n", quote(n), quote(r)));
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Exports the names of a set of articles as plain text, one per line. The names are
+/// sorted and every article is only listed once.
+///
+/// # Examples
+///
+/// ```
+/// use wglib::{export, Article, URL};
+///
+/// let a = Article::new(URL::new("/wiki/Tree").unwrap());
+/// let b = Article::new(URL::new("/wiki/Help!_(film)").unwrap());
+///
+/// assert_eq!(export::export_names(&[a, b]), "Help! (film)\nTree\n");
+/// ```
+pub fn export_names(articles: &[Article]) -> String {
+    let mut names: Vec<_> = articles.iter().map(|a| a.url.get_name()).collect();
+    names.sort();
+    names.dedup();
+    names.into_iter().map(|n| n + "\n").collect()
+}
+
+/// Determines how the nodes of an exported graph are identified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeId {
+    /// The name of the article, e.g. `Help! (film)`.
+    Name,
+    /// The full URL of the article, e.g. `https://en.wikipedia.org/wiki/Help!_(film)`.
+    Url,
+}
+
+impl NodeId {
+    fn of(self, url: &URL) -> String {
+        match self {
+            NodeId::Name => url.get_name(),
+            NodeId::Url => url.to_string(),
+        }
+    }
+}
+
+/// Exports a set of articles as JSON in the node-link format of NetworkX, so it can be
+/// read with `networkx.readwrite.json_graph.node_link_graph`.
+///
+/// Nodes are listed in ascending order of their URLs and identified as given by `id`.
+/// Only references between the given articles become links.
+///
+/// # Examples
+///
+/// ```
+/// use wglib::{export, Article, URL};
+/// use wglib::export::NodeId;
+///
+/// let mut a = Article::new(URL::new("/wiki/Tree").unwrap());
+/// a.references.insert(URL::new("/wiki/Plant").unwrap());
+///
+/// assert_eq!(
+///     export::export_node_link_json(&[a], NodeId::Name),
+///     "{\"directed\":true,\"graph\":{},\"links\":[],\"multigraph\":false,\
+///     \"nodes\":[{\"id\":\"Tree\"}]}"
+/// );
+/// ```
+pub fn export_node_link_json(articles: &[Article], id: NodeId) -> String {
+    let g = Graph::new(articles);
+    let nodes: Vec<_> = g
+        .nodes()
+        .iter()
+        .map(|n| json!({ "id": id.of(n) }))
+        .collect();
+    let mut links = Vec::new();
+    for n in g.nodes() {
+        for r in g.neighbours(n) {
+            links.push(json!({ "source": id.of(n), "target": id.of(r) }));
+        }
+    }
+    json!({
+        "directed": true,
+        "multigraph": false,
+        "graph": {},
+        "nodes": nodes,
+        "links": links,
+    })
+    .to_string()
+}
+
+/// Exports the references between a set of articles as CSV with a `source,target` header
+/// and one row per reference, e.g. for Gephi or pandas. The nodes are identified as given
+/// by `id`.
+///
+/// The rows are sorted by the URLs of their source and target. Only references between
+/// the given articles are exported. Fields that contain commas, quotes or line breaks are
+/// quoted as described in RFC 4180.
+///
+/// # Examples
+///
+/// ```
+/// use wglib::{export, Article, URL};
+/// use wglib::export::NodeId;
+///
+/// let mut a = Article::new(URL::new("/wiki/Paris,_Texas").unwrap());
+/// a.references.insert(URL::new("/wiki/Texas").unwrap());
+/// let b = Article::new(URL::new("/wiki/Texas").unwrap());
+///
+/// assert_eq!(
+///     export::export_edge_list_csv(&[a, b], NodeId::Name),
+///     "source,target\n\"Paris, Texas\",Texas\n"
+/// );
+/// ```
+pub fn export_edge_list_csv(articles: &[Article], id: NodeId) -> String {
+    let field = |u: &URL| {
+        let f = id.of(u);
+        if f.contains(&[',', '"', '\n', '\r'][..]) {
+            format!("\"{}\"", f.replace('"', "\"\""))
+        } else {
+            f
+        }
+    };
+    let g = Graph::new(articles);
+    let mut out = String::from("source,target\n");
+    for n in g.nodes() {
+        for r in g.neighbours(n) {
+            out.push_str(&format!("{},{}\n", field(n), field(r)));
+        }
+    }
+    out
+}
+
+/// Exports the results of Collector::all_pairs_paths as a JSON array. Every path is an
+/// object with the names of its endpoints, its length in steps and the names of all the
+/// articles along it, e.g.
+///
+/// ```json
+/// [{"from":"Tree","to":"Sun","length":2,"path":["Tree","Plant","Sun"]}]
+/// ```
+pub fn export_paths_json(results: &[PathResult]) -> String {
+    let paths: Vec<_> = results
+        .iter()
+        .map(|r| {
+            let names: Vec<_> = r.path.iter().map(|u| u.get_name()).collect();
+            json!({
+                "from": r.from.get_name(),
+                "to": r.to.get_name(),
+                "length": r.path.len().saturating_sub(1),
+                "path": names,
+            })
+        })
+        .collect();
+    serde_json::Value::from(paths).to_string()
+}
+
+/// Exports a set of articles as JSON for a force-directed layout in D3, i.e. an object
+/// with `nodes` (`id` and `group`) and `links` (`source`, `target` and `value`). Nodes are
+/// identified by the names of the articles.
+///
+/// If a root is given, nodes are grouped by their distance from it and nodes that can't
+/// be reached from it are in group -1. Otherwise all the nodes are in group 0. Articles that
+/// reference each other are connected by a single link with value 2, all other links have
+/// value 1.
+///
+/// # Examples
+///
+/// ```
+/// use wglib::{export, Article, URL};
+///
+/// let mut a = Article::new(URL::new("/wiki/Tree").unwrap());
+/// a.references.insert(URL::new("/wiki/Plant").unwrap());
+/// let b = Article::new(URL::new("/wiki/Plant").unwrap());
+///
+/// assert_eq!(
+///     export::export_d3_json(&[a.clone(), b], Some(&a.url)),
+///     "{\"links\":[{\"source\":\"Tree\",\"target\":\"Plant\",\"value\":1}],\
+///     \"nodes\":[{\"group\":1,\"id\":\"Plant\"},{\"group\":0,\"id\":\"Tree\"}]}"
+/// );
+/// ```
+pub fn export_d3_json(articles: &[Article], root: Option<&URL>) -> String {
+    let g = Graph::new(articles);
+    let depths = root.map(|r| g.distances(r));
+    let group = |n: &URL| -> i64 {
+        match &depths {
+            Some(ds) => ds.get(n).map(|d| *d as i64).unwrap_or(-1),
+            None => 0,
+        }
+    };
+    let nodes: Vec<_> = g
+        .nodes()
+        .iter()
+        .map(|n| json!({ "id": n.get_name(), "group": group(n) }))
+        .collect();
+    let mut links = Vec::new();
+    for n in g.nodes() {
+        for r in g.neighbours(n) {
+            let mutual = g.neighbours(r).contains(&n);
+            if mutual && r < n {
+                // This link was already added from the other side.
+                continue;
+            }
+            links.push(json!({
+                "source": n.get_name(),
+                "target": r.get_name(),
+                "value": if mutual { 2 } else { 1 },
+            }));
+        }
+    }
+    json!({ "nodes": nodes, "links": links }).to_string()
+}
+
+/// Writes articles as a JSON array incrementally, e.g. while a crawl is still running, so
+/// a huge crawl does not have to be kept in memory to be exported.
+///
+/// Every article becomes an object with its `id` and the ids of all the articles it
+/// references as `links`. The output is flushed after every article and the array is
+/// closed when the writer is dropped, so even the output of an interrupted crawl is valid
+/// JSON. Use JsonStreamWriter::finish to notice errors while closing the array.
+///
+/// # Examples
+///
+/// ```
+/// use wglib::{Article, JsonStreamWriter, NodeId, URL};
+///
+/// let mut a = Article::new(URL::new("/wiki/Tree").unwrap());
+/// a.references.insert(URL::new("/wiki/Plant").unwrap());
+///
+/// let mut w = JsonStreamWriter::new(Vec::new(), NodeId::Name).unwrap();
+/// w.write_article(&a).unwrap();
+/// let out = w.finish().unwrap();
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     "[\n{\"id\":\"Tree\",\"links\":[\"Plant\"]}\n]\n"
+/// );
+/// ```
+pub struct JsonStreamWriter<W: Write> {
+    out: Option<W>,
+    id: NodeId,
+    empty: bool,
+}
+
+impl<W: Write> JsonStreamWriter<W> {
+    /// Starts the array.
+    pub fn new(mut out: W, id: NodeId) -> io::Result<Self> {
+        out.write_all(b"[\n")?;
+        out.flush()?;
+        Ok(JsonStreamWriter {
+            out: Some(out),
+            id,
+            empty: true,
+        })
+    }
+
+    /// Appends an article to the array. Its links are sorted.
+    pub fn write_article(&mut self, a: &Article) -> io::Result<()> {
+        let mut links: Vec<_> = a.references.iter().collect();
+        links.sort();
+        let links: Vec<_> = links.into_iter().map(|r| self.id.of(r)).collect();
+        let obj = json!({ "id": self.id.of(&a.url), "links": links });
+        let out = self.out.as_mut().expect("Writer is already finished.");
+        if !self.empty {
+            out.write_all(b",\n")?;
+        }
+        out.write_all(obj.to_string().as_bytes())?;
+        out.flush()?;
+        self.empty = false;
+        Ok(())
+    }
+
+    /// Closes the array and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let mut out = self.out.take().expect("Writer is already finished.");
+        JsonStreamWriter::close(&mut out, self.empty)?;
+        Ok(out)
+    }
+
+    fn close(out: &mut W, empty: bool) -> io::Result<()> {
+        out.write_all(if empty { b"]\n" } else { b"\n]\n" })?;
+        out.flush()
+    }
+}
+
+impl<W: Write> Drop for JsonStreamWriter<W> {
+    fn drop(&mut self) {
+        if let Some(out) = self.out.as_mut() {
+            // There is no way to report an error here, see JsonStreamWriter::finish.
+            let _ = JsonStreamWriter::close(out, self.empty);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn pajek_has_vertices_and_arcs() {
+        let arts = articles(&[
+            ("C", &["A", "B"]),
+            ("A", &["B", "Outside"]),
+            ("B", &["A"]),
+            ("Say \"hi\"", &["C"]),
+        ]);
+        let net = export_pajek(&arts);
+        let lines: Vec<_> = net.lines().collect();
+        assert_eq!(lines[0], "*Vertices 4");
+        assert_eq!(
+            &lines[1..5],
+            &["1 \"A\"", "2 \"B\"", "3 \"C\"", "4 \"Say 'hi'\""]
+        );
+        assert_eq!(lines[5], "*Arcs");
+        assert_eq!(&lines[6..], &["1 2", "2 1", "3 1", "3 2", "4 3"]);
+    }
+
+    #[test]
+    fn dot_escapes_names() {
+        let arts = articles(&[
+            ("A", &["B", "Outside"]),
+            ("B", &["A", "Say_\"hi\""]),
+            ("Say_\"hi\"", &["Back\\slash"]),
+            ("Back\\slash", &[]),
+        ]);
+        let dot = export_dot(&arts);
+        let lines: Vec<_> = dot.lines().collect();
+        assert_eq!(lines[0], "digraph {");
+        assert_eq!(
+            &lines[1..5],
+            &[
+                "    \"A\";",
+                "    \"B\";",
+                "    \"Back\\\\slash\";",
+                "    \"Say \\\"hi\\\"\";"
+            ]
+        );
+        assert_eq!(
+            &lines[5..],
+            &[
+                "    \"A\" -> \"B\";",
+                "    \"B\" -> \"A\";",
+                "    \"B\" -> \"Say \\\"hi\\\"\";",
+                "    \"Say \\\"hi\\\"\" -> \"Back\\\\slash\";",
+                "}"
+            ]
+        );
+        assert_eq!(export_dot(&[]), "digraph {\n}\n");
+    }
+
+    #[test]
+    fn edge_list_csv_escapes_fields() {
+        let arts = articles(&[
+            ("Paris,_Texas", &["Texas", "Outside"]),
+            ("Texas", &["Say_\"hi\""]),
+            ("Say_\"hi\"", &[]),
+        ]);
+        assert_eq!(
+            export_edge_list_csv(&arts, NodeId::Name),
+            "source,target\n\
+             \"Paris, Texas\",Texas\n\
+             Texas,\"Say \"\"hi\"\"\"\n"
+        );
+        assert_eq!(
+            export_edge_list_csv(&arts, NodeId::Url),
+            "source,target\n\
+             \"https://en.wikipedia.org/wiki/Paris,_Texas\",https://en.wikipedia.org/wiki/Texas\n\
+             https://en.wikipedia.org/wiki/Texas,\"https://en.wikipedia.org/wiki/Say_\"\"hi\"\"\"\n"
+        );
+        assert_eq!(export_edge_list_csv(&[], NodeId::Name), "source,target\n");
+    }
+
+    #[test]
+    fn names_are_sorted_and_unique() {
+        let arts = articles(&[
+            ("Tree", &["Plant"]),
+            ("Forest", &[]),
+            ("Tree", &[]),
+            ("Bark", &[]),
+        ]);
+        assert_eq!(export_names(&arts), "Bark\nForest\nTree\n");
+        assert_eq!(export_names(&[]), "");
+    }
+
+    #[test]
+    fn node_link_json_references_existing_nodes() {
+        let arts = articles(&[("C", &["A", "B"]), ("A", &["B", "Outside"]), ("B", &["A"])]);
+        for id in [NodeId::Name, NodeId::Url] {
+            let v: serde_json::Value =
+                serde_json::from_str(&export_node_link_json(&arts, id)).unwrap();
+            assert_eq!(v["directed"], true);
+            assert_eq!(v["multigraph"], false);
+            let nodes: HashSet<_> = v["nodes"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|n| n["id"].as_str().unwrap())
+                .collect();
+            assert_eq!(nodes.len(), 3);
+            let links = v["links"].as_array().unwrap();
+            assert_eq!(links.len(), 4);
+            for l in links {
+                assert!(nodes.contains(l["source"].as_str().unwrap()));
+                assert!(nodes.contains(l["target"].as_str().unwrap()));
+            }
+        }
+        let v: serde_json::Value =
+            serde_json::from_str(&export_node_link_json(&arts, NodeId::Url)).unwrap();
+        assert_eq!(v["nodes"][0]["id"], "https://en.wikipedia.org/wiki/A");
+        assert_eq!(v["links"][0]["source"], "https://en.wikipedia.org/wiki/A");
+    }
+
+    #[test]
+    fn json_stream_is_always_valid() {
+        let arts = articles(&[("C", &["A", "B"]), ("A", &["B", "Outside"]), ("B", &["A"])]);
+        let parse = |out: &[u8]| -> Vec<serde_json::Value> {
+            serde_json::from_slice::<serde_json::Value>(out)
+                .unwrap()
+                .as_array()
+                .unwrap()
+                .clone()
+        };
+        // The crawl is interrupted after the first article.
+        let mut out = Vec::new();
+        {
+            let mut w = JsonStreamWriter::new(&mut out, NodeId::Name).unwrap();
+            w.write_article(&arts[0]).unwrap();
+        }
+        let partial = parse(&out);
+        assert_eq!(partial.len(), 1);
+        assert_eq!(partial[0]["id"], "C");
+        assert_eq!(
+            parse(
+                &JsonStreamWriter::new(Vec::new(), NodeId::Url)
+                    .unwrap()
+                    .finish()
+                    .unwrap()
+            )
+            .len(),
+            0
+        );
+        let mut w = JsonStreamWriter::new(Vec::new(), NodeId::Name).unwrap();
+        for a in arts.iter() {
+            w.write_article(a).unwrap();
+        }
+        let full = parse(&w.finish().unwrap());
+        let read: Vec<_> = full
+            .iter()
+            .map(|v| {
+                let mut a = Article::new(url(v["id"].as_str().unwrap()));
+                for l in v["links"].as_array().unwrap() {
+                    a.references.insert(url(l.as_str().unwrap()));
+                }
+                a
+            })
+            .collect();
+        assert_eq!(Graph::new(&read), Graph::new(&arts));
+    }
+
+    #[test]
+    fn paths_json_has_lengths() {
+        let results = vec![
+            PathResult {
+                from: url("Tree"),
+                to: url("Sun"),
+                path: vec![url("Tree"), url("Plant"), url("Sun")],
+            },
+            PathResult {
+                from: url("Sun"),
+                to: url("Tree"),
+                path: vec![],
+            },
+        ];
+        let v: serde_json::Value = serde_json::from_str(&export_paths_json(&results)).unwrap();
+        assert_eq!(
+            v,
+            json!([
+                {"from": "Tree", "to": "Sun", "length": 2, "path": ["Tree", "Plant", "Sun"]},
+                {"from": "Sun", "to": "Tree", "length": 0, "path": []},
+            ])
+        );
+        assert_eq!(export_paths_json(&[]), "[]");
+    }
+
+    #[test]
+    fn d3_groups_are_depths() {
+        let arts = articles(&[
+            ("A", &["B", "C"]),
+            ("B", &["A", "D"]),
+            ("C", &["D"]),
+            ("D", &[]),
+            ("E", &["A"]),
+        ]);
+        let v: serde_json::Value =
+            serde_json::from_str(&export_d3_json(&arts, Some(&url("A")))).unwrap();
+        let groups: HashMap<_, _> = v["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| (n["id"].as_str().unwrap(), n["group"].as_i64().unwrap()))
+            .collect();
+        let expected: HashMap<_, _> = vec![("A", 0), ("B", 1), ("C", 1), ("D", 2), ("E", -1)]
+            .into_iter()
+            .collect();
+        assert_eq!(groups, expected);
+        let links = v["links"].as_array().unwrap();
+        assert_eq!(links.len(), 5);
+        for l in links {
+            assert!(groups.contains_key(l["source"].as_str().unwrap()));
+            assert!(groups.contains_key(l["target"].as_str().unwrap()));
+        }
+        assert_eq!(links[0]["source"], "A");
+        assert_eq!(links[0]["target"], "B");
+        assert_eq!(links[0]["value"], 2);
+        let v: serde_json::Value = serde_json::from_str(&export_d3_json(&arts, None)).unwrap();
+        assert!(v["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|n| n["group"] == 0));
+    }
+}
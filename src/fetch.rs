@@ -0,0 +1,358 @@
+use crate::config::{DEFAULT_TIMEOUT, DEFAULT_USER_AGENT};
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+/// FetchErr is an enum that contains possible error values that could occur
+/// while retrieving the text behind a URL in Fetcher::fetch.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum FetchErr {
+    /// The server responded, but not with a success status code.
+    #[error("Server responded with status {0}.")]
+    Status(u16),
+    /// The request did not get a response at all, e.g. because the connection was reset.
+    #[error("Request failed: {0}")]
+    Request(String),
+    /// There was no complete response within the timeout, see HttpOptions::timeout.
+    #[error("Request timed out.")]
+    Timeout,
+}
+
+/// A Fetcher retrieves the text behind a URL, usually by sending a GET request.
+///
+/// Collectors use an HttpFetcher unless they are given something else, which allows
+/// serving articles from somewhere other than `wikipedia.org`, e.g. from memory in tests.
+pub trait Fetcher: Send + Sync {
+    fn fetch<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<String, FetchErr>>;
+}
+
+impl<F: Fetcher + ?Sized> Fetcher for Arc<F> {
+    fn fetch<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<String, FetchErr>> {
+        (**self).fetch(url)
+    }
+}
+
+/// HttpOptions contains the settings that are passed on to the reqwest client of an
+/// HttpFetcher. The default options are the same as reqwest's defaults, except for the
+/// user agent, which is `DEFAULT_USER_AGENT`, and the timeout, which is `DEFAULT_TIMEOUT`.
+///
+/// These only matter for high-throughput crawling where it pays off to make good use
+/// of HTTP/2 multiplexing and to keep connections to Wikipedia open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpOptions {
+    /// Only use HTTP/2 without negotiating it first.
+    pub http2_prior_knowledge: bool,
+    /// How long idle connections are kept alive in the pool; `None` keeps reqwest's default.
+    pub pool_idle_timeout: Option<Duration>,
+    /// The interval of TCP keep-alive probes; `None` doesn't send any.
+    pub tcp_keepalive: Option<Duration>,
+    /// Whether Nagle's algorithm is disabled on the TCP connections.
+    pub tcp_nodelay: bool,
+    /// The User-Agent header that is sent with every request.
+    pub user_agent: String,
+    /// How long to wait for the whole response to a request; `None` waits forever.
+    pub timeout: Option<Duration>,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        HttpOptions {
+            http2_prior_knowledge: false,
+            pool_idle_timeout: None,
+            tcp_keepalive: None,
+            tcp_nodelay: true,
+            user_agent: String::from(DEFAULT_USER_AGENT),
+            timeout: Some(DEFAULT_TIMEOUT),
+        }
+    }
+}
+
+impl HttpOptions {
+    /// Returns a reqwest::ClientBuilder with all these options applied.
+    pub fn client_builder(&self) -> reqwest::ClientBuilder {
+        let mut b = reqwest::Client::builder()
+            .tcp_keepalive(self.tcp_keepalive)
+            .tcp_nodelay_(self.tcp_nodelay)
+            .user_agent(self.user_agent.as_str());
+        if self.http2_prior_knowledge {
+            b = b.http2_prior_knowledge();
+        }
+        if let Some(d) = self.pool_idle_timeout {
+            b = b.pool_idle_timeout(d);
+        }
+        if let Some(d) = self.timeout {
+            b = b.timeout(d);
+        }
+        b
+    }
+}
+
+/// The default Fetcher which sends actual GET requests using a reqwest client.
+pub struct HttpFetcher {
+    client: reqwest::Client,
+    options: HttpOptions,
+}
+
+impl Default for HttpFetcher {
+    fn default() -> Self {
+        HttpFetcher::new()
+    }
+}
+
+impl HttpFetcher {
+    pub fn new() -> Self {
+        HttpFetcher::with_options(HttpOptions::default())
+    }
+
+    /// Creates a new HttpFetcher whose client is configured with the given options.
+    ///
+    /// # Panics
+    ///
+    /// Just like reqwest::Client::new, if the TLS backend cannot be initialized.
+    pub fn with_options(options: HttpOptions) -> Self {
+        let client = options
+            .client_builder()
+            .build()
+            .expect("Could not build HTTP client.");
+        HttpFetcher { client, options }
+    }
+
+    /// Wraps an existing client. The options of such a fetcher are unknown and
+    /// therefore reported as the default ones.
+    pub fn with_client(client: reqwest::Client) -> Self {
+        HttpFetcher {
+            client,
+            options: HttpOptions::default(),
+        }
+    }
+
+    pub fn options(&self) -> &HttpOptions {
+        &self.options
+    }
+}
+
+impl Fetcher for HttpFetcher {
+    fn fetch<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<String, FetchErr>> {
+        Box::pin(async move {
+            let r = self.client.get(url).send().await?;
+            if !r.status().is_success() {
+                return Err(FetchErr::Status(r.status().as_u16()));
+            }
+            Ok(r.text().await?)
+        })
+    }
+}
+
+/// The kinds of failed requests that may be retried, each with its own backoff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FailureClass {
+    /// The server asked to slow down (429).
+    TooManyRequests,
+    /// The server has a temporary problem (5xx).
+    ServerError,
+    /// There was no response at all, e.g. because the connection was reset.
+    Connection,
+}
+
+impl FetchErr {
+    /// Returns the kind of failure if the same request might succeed if it is sent again,
+    /// or `None` if it would fail again anyway, e.g. because the article does not exist.
+    pub fn class(&self) -> Option<FailureClass> {
+        match self {
+            FetchErr::Status(429) => Some(FailureClass::TooManyRequests),
+            FetchErr::Status(s) if *s >= 500 => Some(FailureClass::ServerError),
+            FetchErr::Status(_) => None,
+            FetchErr::Request(_) | FetchErr::Timeout => Some(FailureClass::Connection),
+        }
+    }
+
+    /// Whether the same request might succeed if it is sent again, see FetchErr::class.
+    pub fn is_transient(&self) -> bool {
+        self.class().is_some()
+    }
+}
+
+/// How often a failed request is repeated and how long to wait before every repetition.
+///
+/// The delay is multiplied by `factor` after every repetition, so a factor of 1 waits the
+/// same time before each of them, and a factor of 2 doubles the time every time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    pub retries: usize,
+    pub delay: Duration,
+    pub factor: u32,
+}
+
+impl Backoff {
+    /// Waits the same time before every repetition.
+    pub fn constant(retries: usize, delay: Duration) -> Self {
+        Backoff {
+            retries,
+            delay,
+            factor: 1,
+        }
+    }
+
+    /// Waits the given time before the first repetition and doubles it for every
+    /// further one, e.g. 500ms, 1s, 2s and so on.
+    pub fn exponential(retries: usize, initial: Duration) -> Self {
+        Backoff {
+            retries,
+            delay: initial,
+            factor: 2,
+        }
+    }
+
+    /// The time to wait before the given repetition, counting from 0.
+    pub fn delay_before(&self, retry: usize) -> Duration {
+        let factor = self.factor.saturating_pow(retry as u32);
+        self.delay.saturating_mul(factor)
+    }
+}
+
+/// A RetryPolicy determines how failed requests are retried depending on the kind of
+/// failure, see FailureClass. Failures without a Backoff are not retried.
+///
+/// The default policy does not retry anything. Collectors use RetryPolicy::exponential
+/// with `DEFAULT_RETRIES` and `DEFAULT_RETRY_DELAY` unless configured otherwise.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use wglib::{Backoff, FailureClass, RetryPolicy};
+///
+/// let p = RetryPolicy::recommended().with(
+///     FailureClass::Connection,
+///     Backoff::exponential(5, Duration::from_millis(100)),
+/// );
+/// let b = p.backoff(FailureClass::Connection).unwrap();
+/// assert_eq!(b.retries, 5);
+/// assert_eq!(b.delay_before(2), Duration::from_millis(400));
+/// assert!(RetryPolicy::default().backoff(FailureClass::Connection).is_none());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RetryPolicy {
+    backoffs: HashMap<FailureClass, Backoff>,
+}
+
+impl RetryPolicy {
+    /// A policy that is polite to an overloaded server, but recovers quickly from lost
+    /// connections. Every kind of failure is retried three times, after
+    ///
+    /// * 5 seconds for FailureClass::TooManyRequests,
+    /// * 1 second for FailureClass::ServerError and
+    /// * 250 milliseconds for FailureClass::Connection.
+    pub fn recommended() -> Self {
+        let backoff = |ms| Backoff::constant(3, Duration::from_millis(ms));
+        RetryPolicy::default()
+            .with(FailureClass::TooManyRequests, backoff(5000))
+            .with(FailureClass::ServerError, backoff(1000))
+            .with(FailureClass::Connection, backoff(250))
+    }
+
+    /// A policy that treats all the kinds of failures the same and waits the same time
+    /// before every repetition.
+    pub fn uniform(retries: usize, delay: Duration) -> Self {
+        RetryPolicy::all(Backoff::constant(retries, delay))
+    }
+
+    /// A policy that treats all the kinds of failures the same and doubles the time it
+    /// waits with every repetition, see Backoff::exponential.
+    pub fn exponential(retries: usize, initial: Duration) -> Self {
+        RetryPolicy::all(Backoff::exponential(retries, initial))
+    }
+
+    /// A policy that uses the same backoff for all the kinds of failures.
+    fn all(b: Backoff) -> Self {
+        RetryPolicy::default()
+            .with(FailureClass::TooManyRequests, b)
+            .with(FailureClass::ServerError, b)
+            .with(FailureClass::Connection, b)
+    }
+
+    /// Sets the backoff for one kind of failure.
+    pub fn with(mut self, class: FailureClass, backoff: Backoff) -> Self {
+        self.backoffs.insert(class, backoff);
+        self
+    }
+
+    /// Returns the backoff for one kind of failure, if it is retried at all.
+    pub fn backoff(&self, class: FailureClass) -> Option<Backoff> {
+        self.backoffs.get(&class).copied()
+    }
+}
+
+impl From<reqwest::Error> for FetchErr {
+    fn from(e: reqwest::Error) -> Self {
+        match e.status() {
+            Some(s) => FetchErr::Status(s.as_u16()),
+            None if e.is_timeout() => FetchErr::Timeout,
+            None => FetchErr::Request(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn http_options_are_applied() {
+        let opts = HttpOptions {
+            http2_prior_knowledge: true,
+            pool_idle_timeout: Some(Duration::from_secs(30)),
+            tcp_keepalive: Some(Duration::from_secs(60)),
+            tcp_nodelay: false,
+            user_agent: String::from("test/1.0 (test@example.com)"),
+            timeout: Some(Duration::from_secs(5)),
+        };
+        let b = format!("{:?}", opts.client_builder());
+        assert!(b.contains("http2_prior_knowledge: true"));
+        assert!(b.contains("test/1.0 (test@example.com)"));
+        assert!(b.contains("timeout: 5s"));
+        assert!(!b.contains("tcp_nodelay"));
+        let f = HttpFetcher::with_options(opts.clone());
+        assert_eq!(f.options(), &opts);
+        let b = format!("{:?}", HttpOptions::default().client_builder());
+        assert!(!b.contains("http2_prior_knowledge"));
+        assert!(b.contains("tcp_nodelay: true"));
+        assert!(b.contains(DEFAULT_USER_AGENT));
+    }
+
+    #[test]
+    fn failures_are_classified() {
+        assert_eq!(
+            FetchErr::Status(429).class(),
+            Some(FailureClass::TooManyRequests)
+        );
+        assert_eq!(
+            FetchErr::Status(503).class(),
+            Some(FailureClass::ServerError)
+        );
+        assert_eq!(
+            FetchErr::Request(String::from("reset")).class(),
+            Some(FailureClass::Connection)
+        );
+        assert_eq!(FetchErr::Timeout.class(), Some(FailureClass::Connection));
+        assert_eq!(FetchErr::Status(404).class(), None);
+    }
+
+    #[test]
+    fn exponential_backoff_doubles_delay() {
+        let b = Backoff::exponential(3, Duration::from_millis(500));
+        let delays: Vec<_> = (0..b.retries).map(|i| b.delay_before(i)).collect();
+        assert_eq!(
+            delays,
+            vec![
+                Duration::from_millis(500),
+                Duration::from_secs(1),
+                Duration::from_secs(2)
+            ]
+        );
+        let b = Backoff::constant(3, Duration::from_millis(500));
+        assert_eq!(b.delay_before(2), Duration::from_millis(500));
+    }
+}
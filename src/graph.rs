@@ -0,0 +1,1126 @@
+use super::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// A directed graph over a set of collected articles.
+///
+/// Every article becomes a node and keeps its full set of references. Only those
+/// references that point to another node of the graph are considered edges, though.
+/// This allows analysing a neighbourhood without any further GET requests.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Graph {
+    references: HashMap<URL, HashSet<URL>>,
+}
+
+/// A breadth-first spanning tree of a graph, i.e. every reachable node together with
+/// the node it was discovered from.
+///
+/// This is a lot sparser than the graph itself and therefore a lot easier to look at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BfsTree {
+    /// The node the search started at. This is the only node without a parent.
+    pub root: URL,
+    /// Maps every other node in the tree to the node it was discovered from.
+    pub parents: HashMap<URL, URL>,
+}
+
+impl Graph {
+    /// Creates a new graph from a list of articles. If an article occurs more than
+    /// once, the references of all its occurrences are combined.
+    pub fn new(articles: &[Article]) -> Self {
+        let mut references: HashMap<URL, HashSet<URL>> = HashMap::new();
+        for a in articles {
+            references
+                .entry(a.url.clone())
+                .or_default()
+                .extend(a.references.iter().cloned());
+        }
+        Graph { references }
+    }
+
+    /// The number of nodes in the graph.
+    pub fn len(&self) -> usize {
+        self.references.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.references.is_empty()
+    }
+
+    pub fn contains(&self, url: &URL) -> bool {
+        self.references.contains_key(url)
+    }
+
+    /// Returns all the nodes of the graph in ascending order.
+    pub fn nodes(&self) -> Vec<&URL> {
+        let mut ns: Vec<_> = self.references.keys().collect();
+        ns.sort();
+        ns
+    }
+
+    /// Returns the nodes a given node has edges to in ascending order. Nodes that are
+    /// not in the graph have no neighbours.
+    pub fn neighbours(&self, url: &URL) -> Vec<&URL> {
+        let mut ns: Vec<_> = match self.references.get(url) {
+            Some(rs) => rs.iter().filter(|r| self.contains(r)).collect(),
+            None => Vec::new(),
+        };
+        ns.sort();
+        ns
+    }
+
+    /// Returns the nodes that have edges to a given node in ascending order, i.e. the
+    /// articles in the graph that link to it. Nodes that are not in the graph have none.
+    ///
+    /// Unlike Wikipedia's backlinks, these only come from the collected articles.
+    pub fn in_neighbours(&self, url: &URL) -> Vec<&URL> {
+        if !self.contains(url) {
+            return Vec::new();
+        }
+        let mut ns: Vec<_> = self
+            .references
+            .iter()
+            .filter(|(_, rs)| rs.contains(url))
+            .map(|(u, _)| u)
+            .collect();
+        ns.sort();
+        ns
+    }
+
+    /// The share of the references of a node that point to other nodes of the graph, i.e.
+    /// how much of an article is about the collected topic. This is 0 for nodes without
+    /// references and for nodes that are not in the graph.
+    pub fn internal_link_ratio(&self, url: &URL) -> f64 {
+        match self.references.get(url) {
+            Some(rs) if !rs.is_empty() => self.neighbours(url).len() as f64 / rs.len() as f64,
+            _ => 0.0,
+        }
+    }
+
+    /// The number of edges in the graph, i.e. the number of references between nodes.
+    pub fn edge_count(&self) -> usize {
+        self.references
+            .values()
+            .map(|rs| rs.iter().filter(|r| self.contains(r)).count())
+            .sum()
+    }
+
+    /// Returns the subgraph that consists of the given nodes and the edges between them.
+    /// Nodes that are not in this graph are ignored.
+    ///
+    /// Like any graph, the subgraph keeps the full references of its nodes, so edges to
+    /// nodes outside the subset are dropped, but Graph::internal_link_ratio still sees them.
+    pub fn induced_subgraph(&self, nodes: &HashSet<URL>) -> Graph {
+        Graph {
+            references: self
+                .references
+                .iter()
+                .filter(|(u, _)| nodes.contains(u))
+                .map(|(u, rs)| (u.clone(), rs.clone()))
+                .collect(),
+        }
+    }
+
+    /// Computes the breadth-first spanning tree starting at the given root. Neighbours
+    /// are visited in ascending order, so the result is deterministic.
+    ///
+    /// Nodes that are not reachable from the root are not part of the tree. If the root
+    /// is not in the graph, there is no tree at all.
+    pub fn bfs_tree(&self, root: &URL) -> Option<BfsTree> {
+        if !self.contains(root) {
+            return None;
+        }
+        let mut parents = HashMap::new();
+        let mut q = VecDeque::new();
+        q.push_back(root);
+        while let Some(v) = q.pop_front() {
+            for n in self.neighbours(v) {
+                if n != root && !parents.contains_key(n) {
+                    parents.insert(n.clone(), v.clone());
+                    q.push_back(n);
+                }
+            }
+        }
+        Some(BfsTree {
+            root: root.clone(),
+            parents,
+        })
+    }
+
+    /// Merges nodes that are the same article after normalizing their URLs again, e.g.
+    /// nodes from old cache data or from a case-sensitive crawl. The references of merged
+    /// nodes are combined and every reference is normalized as well, so edges to any of
+    /// the variants now point to the merged node.
+    ///
+    /// URLs that are no longer valid at all are kept as they are. Returns the number of
+    /// nodes that were merged into others.
+    pub fn dedupe(&mut self) -> usize {
+        let canonical = |u: &URL| {
+            URL::new_in(
+                &u.to_string(),
+                u.domain_config(),
+                CaseSensitivity::default(),
+            )
+            .unwrap_or_else(|_| u.clone())
+        };
+        let before = self.len();
+        let mut references: HashMap<URL, HashSet<URL>> = HashMap::new();
+        for (u, rs) in self.references.drain() {
+            references
+                .entry(canonical(&u))
+                .or_default()
+                .extend(rs.iter().map(canonical));
+        }
+        self.references = references;
+        before - self.len()
+    }
+
+    /// Enumerates simple paths (no node is visited twice) from one node to another that
+    /// consist of exactly `k` edges. Each path contains both endpoints, i.e. `k + 1` nodes.
+    ///
+    /// Paths are found by a depth-first search in ascending order of the nodes and at most
+    /// `limit` of them are returned, because their number grows very quickly with `k`.
+    pub fn paths_of_length(&self, from: &URL, to: &URL, k: usize, limit: usize) -> Vec<Vec<URL>> {
+        let mut res = Vec::new();
+        if self.contains(from) && self.contains(to) && limit > 0 {
+            let mut path = vec![from];
+            self.extend_paths(to, k, limit, &mut path, &mut res);
+        }
+        res
+    }
+
+    /// Tries all the ways to extend the given path to `to` in exactly `k` more edges.
+    fn extend_paths<'a>(
+        &'a self,
+        to: &URL,
+        k: usize,
+        limit: usize,
+        path: &mut Vec<&'a URL>,
+        res: &mut Vec<Vec<URL>>,
+    ) {
+        let v = *path.last().unwrap();
+        if k == 0 {
+            if v == to {
+                res.push(path.iter().map(|u| (*u).clone()).collect());
+            }
+            return;
+        }
+        for n in self.neighbours(v) {
+            // The target may only be the last node and no node may occur twice.
+            if res.len() >= limit || (n == to && k > 1) || path.contains(&n) {
+                continue;
+            }
+            path.push(n);
+            self.extend_paths(to, k - 1, limit, path, res);
+            path.pop();
+        }
+    }
+
+    /// Computes the harmonic centrality of every node, i.e. the sum of the inverse
+    /// distances to all other nodes, divided by the number of other nodes. Nodes that are
+    /// not reachable contribute 0, so unlike closeness centrality this works for graphs
+    /// that are not strongly connected.
+    ///
+    /// Distances are measured along the outgoing edges, so an article that reaches many
+    /// others in few clicks has a high centrality. All values are between 0 and 1.
+    pub fn harmonic_centrality(&self) -> HashMap<URL, f64> {
+        let others = self.len().saturating_sub(1).max(1) as f64;
+        self.references
+            .keys()
+            .map(|u| {
+                let sum: f64 = self
+                    .distances(u)
+                    .values()
+                    .filter(|d| **d > 0)
+                    .map(|d| 1.0 / *d as f64)
+                    .sum();
+                (u.clone(), sum / others)
+            })
+            .collect()
+    }
+
+    /// The number of edges pointing to every node of the graph.
+    pub fn in_degrees(&self) -> HashMap<&URL, usize> {
+        let mut ds: HashMap<&URL, usize> = self.references.keys().map(|u| (u, 0)).collect();
+        for rs in self.references.values() {
+            for r in rs {
+                if let Some(d) = ds.get_mut(r) {
+                    *d += 1;
+                }
+            }
+        }
+        ds
+    }
+
+    /// The length of the longest shortest path between any two nodes, i.e. the number of
+    /// clicks it takes at most to get from one article to another. Pairs of nodes without
+    /// a path between them are ignored.
+    ///
+    /// This searches from every node, so it takes quadratic time.
+    pub fn diameter(&self) -> usize {
+        self.references
+            .keys()
+            .filter_map(|u| self.distances(u).into_values().max())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Splits the graph into its weakly connected components, i.e. the parts that are
+    /// connected if the direction of the edges is ignored.
+    ///
+    /// Every component is sorted and the components are ordered by descending size, ties
+    /// in ascending order of their first node.
+    pub fn weakly_connected_components(&self) -> Vec<Vec<URL>> {
+        let mut undirected: HashMap<&URL, Vec<&URL>> = HashMap::new();
+        for u in self.references.keys() {
+            for n in self.neighbours(u) {
+                undirected.entry(u).or_default().push(n);
+                undirected.entry(n).or_default().push(u);
+            }
+        }
+        let mut seen = HashSet::new();
+        let mut components = Vec::new();
+        for u in self.nodes() {
+            if !seen.insert(u) {
+                continue;
+            }
+            let mut c = vec![u.clone()];
+            let mut stack = vec![u];
+            while let Some(v) = stack.pop() {
+                for n in undirected.get(v).into_iter().flatten() {
+                    if seen.insert(n) {
+                        c.push((*n).clone());
+                        stack.push(n);
+                    }
+                }
+            }
+            c.sort();
+            components.push(c);
+        }
+        components.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        components
+    }
+
+    /// Returns the subgraph that consists of all the nodes on any shortest path from one
+    /// node to another, see Graph::induced_subgraph. If there is no path, the subgraph is
+    /// empty.
+    pub fn shortest_path_subgraph(&self, from: &URL, to: &URL) -> Graph {
+        let forward = self.distances(from);
+        let mut nodes = HashSet::new();
+        if let Some(d) = forward.get(to) {
+            let backward = self.distances_to(to);
+            for (u, f) in forward.iter() {
+                if backward.get(u).map(|b| f + b) == Some(*d) {
+                    nodes.insert((*u).clone());
+                }
+            }
+        }
+        self.induced_subgraph(&nodes)
+    }
+
+    /// Computes the distance from every node that can reach the given node to it.
+    fn distances_to(&self, to: &URL) -> HashMap<&URL, usize> {
+        let mut sources: HashMap<&URL, Vec<&URL>> = HashMap::new();
+        for u in self.references.keys() {
+            for n in self.neighbours(u) {
+                sources.entry(n).or_default().push(u);
+            }
+        }
+        let mut dist = HashMap::new();
+        let to = match self.references.get_key_value(to) {
+            Some((u, _)) => u,
+            None => return dist,
+        };
+        dist.insert(to, 0);
+        let mut q = VecDeque::new();
+        q.push_back(to);
+        while let Some(v) = q.pop_front() {
+            let d = dist[v] + 1;
+            for n in sources.get(v).into_iter().flatten() {
+                if !dist.contains_key(n) {
+                    dist.insert(n, d);
+                    q.push_back(n);
+                }
+            }
+        }
+        dist
+    }
+
+    /// Splits the graph into its strongly connected components, i.e. the largest groups of
+    /// nodes that can all reach each other, using Kosaraju's algorithm. Every node is in
+    /// exactly one component, possibly on its own.
+    ///
+    /// Every component is sorted and the components are ordered by descending size, ties
+    /// in ascending order of their first node.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<URL>> {
+        // Order the nodes by the time a depth-first search is finished with them.
+        let mut finished = Vec::new();
+        let mut seen = HashSet::new();
+        for u in self.nodes() {
+            if !seen.insert(u) {
+                continue;
+            }
+            let mut stack = vec![(u, self.neighbours(u).into_iter())];
+            while let Some((v, ns)) = stack.last_mut() {
+                match ns.find(|n| !seen.contains(n)) {
+                    Some(n) => {
+                        seen.insert(n);
+                        stack.push((n, self.neighbours(n).into_iter()));
+                    }
+                    None => {
+                        finished.push(*v);
+                        stack.pop();
+                    }
+                }
+            }
+        }
+        // Nodes that reach the last finished node in the reversed graph form a component.
+        let mut sources: HashMap<&URL, Vec<&URL>> = HashMap::new();
+        for u in self.references.keys() {
+            for n in self.neighbours(u) {
+                sources.entry(n).or_default().push(u);
+            }
+        }
+        let mut assigned = HashSet::new();
+        let mut components = Vec::new();
+        for u in finished.into_iter().rev() {
+            if !assigned.insert(u) {
+                continue;
+            }
+            let mut c = vec![u.clone()];
+            let mut stack = vec![u];
+            while let Some(v) = stack.pop() {
+                for n in sources.get(v).into_iter().flatten() {
+                    if assigned.insert(n) {
+                        c.push((*n).clone());
+                        stack.push(n);
+                    }
+                }
+            }
+            c.sort();
+            components.push(c);
+        }
+        components.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        components
+    }
+
+    /// Estimates the distribution of the distances between the nodes by sampling random
+    /// ordered pairs of distinct nodes. Returns how many of the sampled pairs have each
+    /// distance; pairs without a path are counted under `None`.
+    ///
+    /// The pairs are drawn from a random number generator with the given seed, so the
+    /// result is reproducible. Graphs with fewer than two nodes have no pairs.
+    pub fn separation_histogram(
+        &self,
+        sample_pairs: usize,
+        seed: u64,
+    ) -> HashMap<Option<usize>, usize> {
+        let mut hist = HashMap::new();
+        let nodes = self.nodes();
+        if nodes.len() < 2 {
+            return hist;
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut dists = HashMap::new(); // The distances from every node that was drawn
+        for _ in 0..sample_pairs {
+            let from = nodes[rng.gen_range(0, nodes.len())];
+            // Draw from all the other nodes.
+            let mut to = rng.gen_range(0, nodes.len() - 1);
+            if nodes[to] == from {
+                to = nodes.len() - 1;
+            }
+            let d = dists
+                .entry(from)
+                .or_insert_with(|| self.distances(from))
+                .get(nodes[to])
+                .copied();
+            *hist.entry(d).or_default() += 1;
+        }
+        hist
+    }
+
+    /// Computes the distance from the given node to every node that is reachable from it,
+    /// including the node itself at distance 0.
+    pub(crate) fn distances(&self, from: &URL) -> HashMap<&URL, usize> {
+        let mut dist = HashMap::new();
+        let from = match self.references.get_key_value(from) {
+            Some((u, _)) => u,
+            None => return dist,
+        };
+        dist.insert(from, 0);
+        let mut q = VecDeque::new();
+        q.push_back(from);
+        while let Some(v) = q.pop_front() {
+            let d = dist[v] + 1;
+            for n in self.neighbours(v) {
+                if !dist.contains_key(n) {
+                    dist.insert(n, d);
+                    q.push_back(n);
+                }
+            }
+        }
+        dist
+    }
+
+    /// Finds the shortest cycle through the given node, i.e. the shortest path of at least
+    /// one edge from the node back to itself. The cycle starts and ends with the node, so a
+    /// self-reference gives `[url, url]`.
+    ///
+    /// Neighbours are visited in ascending order, so the result is deterministic. If there
+    /// is no such cycle or the node is not in the graph, there is no result.
+    pub fn shortest_cycle(&self, url: &URL) -> Option<Vec<URL>> {
+        if !self.contains(url) {
+            return None;
+        }
+        let mut parents: HashMap<&URL, &URL> = HashMap::new();
+        let mut q = VecDeque::new();
+        q.push_back(url);
+        while let Some(v) = q.pop_front() {
+            for n in self.neighbours(v) {
+                if n == url {
+                    let mut cycle = vec![url.clone(), v.clone()];
+                    let mut x = v;
+                    while x != url {
+                        x = parents[x];
+                        cycle.push(x.clone());
+                    }
+                    cycle.reverse();
+                    return Some(cycle);
+                }
+                if !parents.contains_key(n) {
+                    parents.insert(n, v);
+                    q.push_back(n);
+                }
+            }
+        }
+        None
+    }
+
+    /// Finds the most surprising of the shortest paths between two nodes, i.e. the one whose
+    /// intermediate nodes are the least popular. Popularity is measured by the in-degree, so
+    /// among all the shortest paths the one with the smallest sum of in-degrees of its
+    /// intermediate nodes is chosen. Remaining ties are broken in favour of smaller nodes.
+    ///
+    /// The path contains both endpoints. If there is no path, there is no result.
+    pub fn most_obscure_shortest_path(&self, from: &URL, to: &URL) -> Option<Vec<URL>> {
+        let dist = self.distances(from);
+        let target = dist.get(to)?;
+        let in_degrees = self.in_degrees();
+        // The least popularity of a shortest path to every node and where it comes from
+        let mut best: HashMap<&URL, (usize, Option<&URL>)> = HashMap::new();
+        let (from, _) = self.references.get_key_value(from)?;
+        best.insert(from, (0, None));
+        let mut layer = vec![from];
+        for d in 1..=*target {
+            let mut next = Vec::new();
+            for v in layer {
+                let cost = best[v].0 + if d > 1 { in_degrees[v] } else { 0 };
+                for n in self.neighbours(v) {
+                    if dist[n] != d {
+                        continue;
+                    }
+                    match best.get(n) {
+                        Some((c, Some(p))) if (*c, *p) <= (cost, v) => {}
+                        Some(_) => {
+                            best.insert(n, (cost, Some(v)));
+                        }
+                        None => {
+                            best.insert(n, (cost, Some(v)));
+                            next.push(n);
+                        }
+                    }
+                }
+            }
+            layer = next;
+        }
+        let mut path = vec![to.clone()];
+        let mut v = to;
+        while let (_, Some(p)) = best[v] {
+            path.push(p.clone());
+            v = p;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Finds pairs of nodes that might be about the same topic and could be merged: they
+    /// reference each other and their other neighbours are mostly the same. Similarity is
+    /// measured by the Jaccard index of the neighbours of both nodes, not counting the
+    /// nodes themselves, and has to be above the given threshold.
+    ///
+    /// Every pair is only listed once, the smaller node first, and pairs are in ascending
+    /// order.
+    pub fn merge_candidates(&self, threshold: f64) -> Vec<(URL, URL)> {
+        let mut res = Vec::new();
+        for a in self.nodes() {
+            let na: HashSet<_> = self.neighbours(a).into_iter().collect();
+            for b in na.iter().filter(|b| a < **b) {
+                let nb: HashSet<_> = self.neighbours(b).into_iter().collect();
+                if !nb.contains(a) {
+                    continue;
+                }
+                let others = |n: &&&URL| **n != a && *n != b;
+                let union = na.union(&nb).filter(others).count();
+                let shared = na.intersection(&nb).filter(others).count();
+                if union > 0 && shared as f64 / union as f64 > threshold {
+                    res.push((a.clone(), (*b).clone()));
+                }
+            }
+        }
+        res.sort();
+        res
+    }
+
+    /// Ranks the nodes by how much they are linked to from close to the given seed, which
+    /// favours the articles about the seed's topic over hubs that everything links to.
+    ///
+    /// This is an in-degree where every edge is weighted by the distance `d` of the node it
+    /// comes from to the seed:
+    ///
+    /// ```text
+    /// importance(v) = sum of 1 / (d(u) + 1) over all edges u -> v
+    /// ```
+    ///
+    /// So a link from the seed counts 1, a link from one of its neighbours 1/2 and so on.
+    /// Links from nodes that can't be reached from the seed don't count at all. Every node
+    /// of the graph is part of the result, even if its importance is 0.
+    pub fn layered_importance(&self, seed: &URL) -> HashMap<URL, f64> {
+        let dist = self.distances(seed);
+        let mut importance: HashMap<URL, f64> =
+            self.references.keys().map(|u| (u.clone(), 0.0)).collect();
+        for (u, d) in dist.iter() {
+            for v in self.neighbours(u) {
+                *importance.get_mut(v).unwrap() += 1.0 / (*d + 1) as f64;
+            }
+        }
+        importance
+    }
+
+    /// Finds a smallest set of intermediate nodes whose removal disconnects `to` from `from`,
+    /// i.e. the articles every path between them has to go through. This is computed as a
+    /// minimum cut in a flow network where every node is split into an entry and an exit
+    /// connected by an edge of capacity 1.
+    ///
+    /// The cut is sorted in ascending order. If there is no path to begin with, the cut is
+    /// empty. If `from` links to `to` directly or they are the same node, no set of
+    /// intermediate nodes can separate them and there is no result.
+    pub fn min_cut(&self, from: &URL, to: &URL) -> Option<Vec<URL>> {
+        if from == to || self.neighbours(from).contains(&to) {
+            return None;
+        }
+        let nodes = self.nodes();
+        let index: HashMap<&URL, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+        let (s, t) = match (index.get(from), index.get(to)) {
+            (Some(s), Some(t)) => (2 * s + 1, 2 * t),
+            _ => return Some(Vec::new()),
+        };
+        // Node i enters at 2i and leaves at 2i + 1. Edges between nodes can't be cut.
+        let unbounded = nodes.len();
+        let mut capacity: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut adjacent = vec![Vec::new(); 2 * nodes.len()];
+        let mut add = |x: usize, y: usize, c: usize| {
+            capacity.insert((x, y), c);
+            capacity.entry((y, x)).or_insert(0);
+            adjacent[x].push(y);
+            adjacent[y].push(x);
+        };
+        for (i, n) in nodes.iter().enumerate() {
+            add(2 * i, 2 * i + 1, 1);
+            for m in self.neighbours(n) {
+                add(2 * i + 1, 2 * index[m], unbounded);
+            }
+        }
+        // Augments along shortest paths until the sink is unreachable, then returns what
+        // can still be reached from the source.
+        loop {
+            let mut parents = HashMap::new();
+            let mut q = VecDeque::new();
+            q.push_back(s);
+            parents.insert(s, s);
+            while let Some(x) = q.pop_front() {
+                for &y in adjacent[x].iter() {
+                    if capacity[&(x, y)] > 0 && !parents.contains_key(&y) {
+                        parents.insert(y, x);
+                        q.push_back(y);
+                    }
+                }
+            }
+            if !parents.contains_key(&t) {
+                let mut cut: Vec<URL> = (0..nodes.len())
+                    .filter(|i| {
+                        parents.contains_key(&(2 * i)) && !parents.contains_key(&(2 * i + 1))
+                    })
+                    .map(|i| nodes[i].clone())
+                    .collect();
+                cut.sort();
+                return Some(cut);
+            }
+            // Every augmenting path goes through at least one inner edge of capacity 1.
+            let mut y = t;
+            while y != s {
+                let x = parents[&y];
+                *capacity.get_mut(&(x, y)).unwrap() -= 1;
+                *capacity.get_mut(&(y, x)).unwrap() += 1;
+                y = x;
+            }
+        }
+    }
+
+    /// Labels every node with the seed it is closest to, i.e. splits the graph into the
+    /// territories of the given seeds. Distances are measured along the edges, starting
+    /// at the seeds.
+    ///
+    /// Ties are broken in favour of the seed that comes first in the given slice. Nodes
+    /// that are not reachable from any seed, as well as seeds that are not in the graph,
+    /// are not part of the result.
+    pub fn nearest_seed(&self, seeds: &[URL]) -> HashMap<URL, URL> {
+        let mut owner = HashMap::new(); // Index of the closest seed of every reached node
+        let mut ts = Vec::new(); // Nodes that were reached in the last step
+        for (i, s) in seeds.iter().enumerate() {
+            if self.contains(s) && !owner.contains_key(s) {
+                owner.insert(s, i);
+                ts.push(s);
+            }
+        }
+        while !ts.is_empty() {
+            // A node may be reached from several nodes in the same step; the smallest
+            // seed index among them wins.
+            let mut new_ts: HashMap<&URL, usize> = HashMap::new();
+            for v in ts {
+                let o = owner[v];
+                for n in self.neighbours(v) {
+                    if !owner.contains_key(n) {
+                        let e = new_ts.entry(n).or_insert(o);
+                        *e = (*e).min(o);
+                    }
+                }
+            }
+            owner.extend(new_ts.iter().map(|(n, o)| (*n, *o)));
+            ts = new_ts.into_keys().collect();
+        }
+        owner
+            .into_iter()
+            .map(|(n, i)| (n.clone(), seeds[i].clone()))
+            .collect()
+    }
+}
+
+impl BfsTree {
+    /// The number of nodes in the tree, including the root.
+    pub fn len(&self) -> usize {
+        self.parents.len() + 1
+    }
+
+    /// A tree always contains at least its root.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Returns all the edges of the tree as `(parent, child)` pairs in ascending order.
+    pub fn edges(&self) -> Vec<(URL, URL)> {
+        let mut es: Vec<_> = self
+            .parents
+            .iter()
+            .map(|(c, p)| (p.clone(), c.clone()))
+            .collect();
+        es.sort();
+        es
+    }
+
+    /// Converts the tree back into articles whose references are exactly the tree edges.
+    /// That way the tree can be handled just like any other set of collected articles.
+    pub fn articles(&self) -> Vec<Article> {
+        let mut arts = HashMap::new();
+        arts.insert(self.root.clone(), Article::new(self.root.clone()));
+        for (c, p) in self.parents.iter() {
+            arts.entry(c.clone())
+                .or_insert_with(|| Article::new(c.clone()));
+            arts.entry(p.clone())
+                .or_insert_with(|| Article::new(p.clone()))
+                .references
+                .insert(c.clone());
+        }
+        let mut arts: Vec<_> = arts.into_values().collect();
+        arts.sort_by(|a, b| a.url.cmp(&b.url));
+        arts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::*;
+
+    #[test]
+    fn bfs_tree_spans_graph() {
+        let g = graph(&[
+            ("A", &["B", "C"]),
+            ("B", &["C", "D", "Outside"]),
+            ("C", &["A", "D"]),
+            ("D", &["B"]),
+        ]);
+        let t = g.bfs_tree(&url("A")).unwrap();
+        assert_eq!(t.root, url("A"));
+        assert_eq!(t.len(), g.len());
+        assert_eq!(t.edges().len(), g.len() - 1);
+        assert!(!t.parents.contains_key(&url("A")));
+        assert_eq!(t.parents[&url("D")], url("B"));
+    }
+
+    #[test]
+    fn dedupe_merges_variants() {
+        let variant = URL::new_with_case("/wiki/apple", CaseSensitivity::Sensitive).unwrap();
+        let mut arts = articles(&[("Apple", &["Tree"]), ("Tree", &["Apple"]), ("Pie", &[])]);
+        let mut a = Article::new(variant.clone());
+        a.references.insert(url("Pie"));
+        arts.push(a);
+        arts[2].references.insert(variant.clone());
+        let mut g = Graph::new(&arts);
+        assert_eq!(g.len(), 4);
+        assert_eq!(g.dedupe(), 1);
+        assert_eq!(g.len(), 3);
+        assert!(!g.contains(&variant));
+        assert_eq!(g.neighbours(&url("Apple")), vec![&url("Pie"), &url("Tree")]);
+        assert_eq!(g.neighbours(&url("Pie")), vec![&url("Apple")]);
+        assert_eq!(g.edge_count(), 4);
+        assert_eq!(g.dedupe(), 0);
+    }
+
+    #[test]
+    fn in_neighbours_link_to_node() {
+        let g = graph(&[
+            ("Apple", &["Tree", "Pie"]),
+            ("Tree", &["Apple"]),
+            ("Pie", &["Apple", "Cake"]),
+            ("Orchard", &["Tree", "Apple"]),
+        ]);
+        assert_eq!(
+            g.in_neighbours(&url("Apple")),
+            vec![&url("Orchard"), &url("Pie"), &url("Tree")]
+        );
+        assert!(g.in_neighbours(&url("Orchard")).is_empty());
+        assert!(g.in_neighbours(&url("Cake")).is_empty());
+    }
+
+    #[test]
+    fn paths_of_length_are_simple() {
+        let g = graph(&[
+            ("A", &["B", "C", "D"]),
+            ("B", &["C", "D"]),
+            ("C", &["B", "D", "A"]),
+            ("D", &[]),
+        ]);
+        let names = |ps: Vec<Vec<URL>>| -> Vec<String> {
+            ps.iter()
+                .map(|p| p.iter().map(|u| u.get_name()).collect::<Vec<_>>().join(""))
+                .collect()
+        };
+        assert_eq!(
+            names(g.paths_of_length(&url("A"), &url("D"), 1, 10)),
+            vec!["AD"]
+        );
+        assert_eq!(
+            names(g.paths_of_length(&url("A"), &url("D"), 2, 10)),
+            vec!["ABD", "ACD"]
+        );
+        assert_eq!(
+            names(g.paths_of_length(&url("A"), &url("D"), 3, 10)),
+            vec!["ABCD", "ACBD"]
+        );
+        assert_eq!(
+            names(g.paths_of_length(&url("A"), &url("D"), 3, 1)),
+            vec!["ABCD"]
+        );
+        assert!(g.paths_of_length(&url("A"), &url("D"), 4, 10).is_empty());
+        assert_eq!(
+            names(g.paths_of_length(&url("A"), &url("A"), 0, 10)),
+            vec!["A"]
+        );
+    }
+
+    #[test]
+    fn nearest_seed_partitions_graph() {
+        let g = graph(&[
+            ("A", &["B"]),
+            ("B", &["C"]),
+            ("C", &["F"]),
+            ("E", &["D"]),
+            ("D", &["C", "A"]),
+            ("F", &[]),
+            ("G", &["A"]),
+        ]);
+        let ns = g.nearest_seed(&[url("A"), url("E")]);
+        assert_eq!(ns.len(), 6);
+        assert_eq!(ns[&url("A")], url("A"));
+        assert_eq!(ns[&url("B")], url("A"));
+        assert_eq!(ns[&url("D")], url("E"));
+        // C is two steps away from both seeds.
+        assert_eq!(ns[&url("C")], url("A"));
+        assert_eq!(ns[&url("F")], url("A"));
+        assert!(!ns.contains_key(&url("G")));
+        let ns = g.nearest_seed(&[url("E"), url("A")]);
+        assert_eq!(ns[&url("C")], url("E"));
+        assert_eq!(ns[&url("A")], url("A"));
+    }
+
+    #[test]
+    fn harmonic_centrality_of_disconnected_graph() {
+        let g = graph(&[
+            ("A", &["B"]),
+            ("B", &["C"]),
+            ("C", &["Outside"]),
+            ("D", &["E"]),
+            ("E", &["D"]),
+        ]);
+        let hc = g.harmonic_centrality();
+        assert_eq!(hc.len(), 5);
+        // A reaches B in one step and C in two: (1 + 1/2) / 4
+        assert_eq!(hc[&url("A")], 0.375);
+        assert_eq!(hc[&url("B")], 0.25);
+        assert_eq!(hc[&url("C")], 0.0);
+        assert_eq!(hc[&url("D")], 0.25);
+        assert_eq!(hc[&url("E")], 0.25);
+        assert!(graph(&[]).harmonic_centrality().is_empty());
+        assert_eq!(
+            graph(&[("A", &["A"])]).harmonic_centrality()[&url("A")],
+            0.0
+        );
+    }
+
+    #[test]
+    fn weakly_connected_components_ignore_direction() {
+        let g = graph(&[
+            ("A", &["B"]),
+            ("C", &["B", "Outside"]),
+            ("B", &[]),
+            ("D", &["E"]),
+            ("E", &[]),
+            ("F", &[]),
+        ]);
+        let cs = g.weakly_connected_components();
+        assert_eq!(
+            cs,
+            vec![
+                vec![url("A"), url("B"), url("C")],
+                vec![url("D"), url("E")],
+                vec![url("F")],
+            ]
+        );
+        assert_eq!(g.diameter(), 1);
+    }
+
+    #[test]
+    fn shortest_cycle_finds_triangle() {
+        let g = graph(&[
+            ("A", &["B", "D"]),
+            ("B", &["C"]),
+            ("C", &["A", "E"]),
+            ("D", &["E"]),
+            ("E", &["F"]),
+            ("F", &["G"]),
+            ("G", &["A"]),
+            ("H", &["H", "A"]),
+        ]);
+        assert_eq!(
+            g.shortest_cycle(&url("A")),
+            Some(vec![url("A"), url("B"), url("C"), url("A")])
+        );
+        assert_eq!(g.shortest_cycle(&url("H")), Some(vec![url("H"), url("H")]));
+        assert_eq!(g.shortest_cycle(&url("E")).unwrap().len(), 6);
+        let g = graph(&[("A", &["B"]), ("B", &["C"]), ("C", &[])]);
+        assert_eq!(g.shortest_cycle(&url("A")), None);
+        assert_eq!(g.shortest_cycle(&url("Outside")), None);
+    }
+
+    #[test]
+    fn internal_link_ratio_counts_references_in_set() {
+        let g = graph(&[
+            ("A", &["B", "C", "Outside", "Elsewhere"]),
+            ("B", &["A"]),
+            ("C", &[]),
+        ]);
+        assert_eq!(g.internal_link_ratio(&url("A")), 0.5);
+        assert_eq!(g.internal_link_ratio(&url("B")), 1.0);
+        assert_eq!(g.internal_link_ratio(&url("C")), 0.0);
+        assert_eq!(g.internal_link_ratio(&url("Outside")), 0.0);
+    }
+
+    #[test]
+    fn most_obscure_shortest_path_avoids_hubs() {
+        let g = graph(&[
+            ("A", &["Hub", "Niche"]),
+            ("Hub", &["B"]),
+            ("Niche", &["B"]),
+            ("B", &[]),
+            ("X", &["Hub"]),
+            ("Y", &["Hub"]),
+            ("Z", &["Niche", "B"]),
+        ]);
+        let names = |p: Vec<URL>| -> Vec<String> { p.iter().map(|u| u.get_name()).collect() };
+        assert_eq!(
+            names(g.most_obscure_shortest_path(&url("A"), &url("B")).unwrap()),
+            ["A", "Niche", "B"]
+        );
+        // A shorter path is always preferred, no matter how popular it is.
+        assert_eq!(
+            names(g.most_obscure_shortest_path(&url("Z"), &url("B")).unwrap()),
+            ["Z", "B"]
+        );
+        assert_eq!(
+            names(g.most_obscure_shortest_path(&url("A"), &url("A")).unwrap()),
+            ["A"]
+        );
+        assert!(g.most_obscure_shortest_path(&url("B"), &url("A")).is_none());
+    }
+
+    #[test]
+    fn induced_subgraph_drops_excluded_edges() {
+        let g = graph(&[
+            ("A", &["B", "C"]),
+            ("B", &["C", "D"]),
+            ("C", &["A"]),
+            ("D", &["A"]),
+        ]);
+        let nodes: HashSet<_> = vec![url("A"), url("B"), url("Outside")]
+            .into_iter()
+            .collect();
+        let sub = g.induced_subgraph(&nodes);
+        assert_eq!(sub.nodes(), vec![&url("A"), &url("B")]);
+        assert_eq!(sub.neighbours(&url("A")), vec![&url("B")]);
+        assert!(sub.neighbours(&url("B")).is_empty());
+        assert_eq!(sub.edge_count(), 1);
+        assert_eq!(g.edge_count(), 6);
+    }
+
+    #[test]
+    fn merge_candidates_share_neighbours() {
+        let g = graph(&[
+            ("Car", &["Automobile", "Wheel", "Engine", "Road"]),
+            ("Automobile", &["Car", "Wheel", "Engine", "Road"]),
+            ("Wheel", &["Car", "Road"]),
+            ("Road", &["Car", "Wheel"]),
+            ("Engine", &[]),
+        ]);
+        assert_eq!(
+            g.merge_candidates(0.9),
+            vec![(url("Automobile"), url("Car")), (url("Road"), url("Wheel"))]
+        );
+        // Car and Wheel only share Road.
+        assert_eq!(g.merge_candidates(0.3).len(), 4);
+        assert!(g.merge_candidates(1.0).is_empty());
+    }
+
+    #[test]
+    fn shortest_path_subgraph_is_union_of_shortest_paths() {
+        let g = graph(&[
+            ("A", &["B", "C", "D"]),
+            ("B", &["E"]),
+            ("C", &["E", "F"]),
+            ("D", &["F", "G"]),
+            ("E", &["T"]),
+            ("F", &["T"]),
+            ("G", &["H"]),
+            ("H", &["T"]),
+            ("T", &["A"]),
+        ]);
+        let sub = g.shortest_path_subgraph(&url("A"), &url("T"));
+        let names: Vec<_> = sub.nodes().iter().map(|u| u.get_name()).collect();
+        assert_eq!(names, ["A", "B", "C", "D", "E", "F", "T"]);
+        assert_eq!(sub.edge_count(), 10);
+        assert!(g
+            .shortest_path_subgraph(&url("T"), &url("Outside"))
+            .is_empty());
+    }
+
+    #[test]
+    fn strongly_connected_components_of_cycle() {
+        let g = graph(&[
+            ("A", &["B", "D"]),
+            ("B", &["C"]),
+            ("C", &["A", "E"]),
+            ("D", &["E"]),
+            ("E", &["Outside"]),
+            ("F", &["A", "F"]),
+        ]);
+        assert_eq!(
+            g.strongly_connected_components(),
+            vec![
+                vec![url("A"), url("B"), url("C")],
+                vec![url("D")],
+                vec![url("E")],
+                vec![url("F")],
+            ]
+        );
+    }
+
+    #[test]
+    fn separation_histogram_is_reproducible() {
+        let g = graph(&[("A", &["B"]), ("B", &["C"]), ("C", &["A"]), ("D", &["A"])]);
+        let hist = g.separation_histogram(1000, 42);
+        assert_eq!(hist.values().sum::<usize>(), 1000);
+        assert_eq!(hist, g.separation_histogram(1000, 42));
+        // 4 pairs are 1 step apart, 4 pairs 2 steps, 1 pair 3 steps and 3 pairs are unreachable.
+        let mut keys: Vec<_> = hist.keys().copied().collect();
+        keys.sort();
+        assert_eq!(keys, vec![None, Some(1), Some(2), Some(3)]);
+        assert!(hist[&None] > hist[&Some(3)]);
+        assert!(hist[&Some(1)] > hist[&Some(3)]);
+        assert!(graph(&[("A", &[])]).separation_histogram(10, 0).is_empty());
+    }
+
+    #[test]
+    fn bfs_tree_skips_unreachable() {
+        let g = graph(&[("A", &["B"]), ("B", &[]), ("C", &["A"])]);
+        let t = g.bfs_tree(&url("A")).unwrap();
+        assert_eq!(t.len(), 2);
+        assert!(g.bfs_tree(&url("Outside")).is_none());
+    }
+
+    #[test]
+    fn min_cut_finds_bridge() {
+        let g = graph(&[
+            ("A", &["B", "C"]),
+            ("B", &["D"]),
+            ("C", &["D"]),
+            ("D", &["E", "F"]),
+            ("E", &["G"]),
+            ("F", &["G"]),
+            ("G", &["A"]),
+        ]);
+        assert_eq!(g.min_cut(&url("A"), &url("G")), Some(vec![url("D")]));
+        assert_eq!(
+            g.min_cut(&url("A"), &url("D")),
+            Some(vec![url("B"), url("C")])
+        );
+        assert_eq!(g.min_cut(&url("A"), &url("B")), None);
+        let g = graph(&[("A", &["B"]), ("B", &[]), ("C", &["A"])]);
+        assert_eq!(g.min_cut(&url("A"), &url("C")), Some(vec![]));
+        assert_eq!(g.min_cut(&url("A"), &url("Outside")), Some(vec![]));
+    }
+
+    #[test]
+    fn layered_importance_favours_close_linkers() {
+        let g = graph(&[
+            ("S", &["A", "B"]),
+            ("A", &["C", "H"]),
+            ("B", &["C", "H"]),
+            ("C", &["H", "S"]),
+            ("H", &[]),
+            ("X", &["H", "C"]),
+        ]);
+        let importance = g.layered_importance(&url("S"));
+        let expected = vec![
+            ("S", 1.0 / 3.0),
+            ("A", 1.0),
+            ("B", 1.0),
+            ("C", 0.5 + 0.5),
+            ("H", 0.5 + 0.5 + 1.0 / 3.0),
+            ("X", 0.0),
+        ];
+        assert_eq!(importance.len(), expected.len());
+        for (n, score) in expected {
+            assert!((importance[&url(n)] - score).abs() < 1e-9, "{}", n);
+        }
+    }
+}
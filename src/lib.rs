@@ -1,40 +1,116 @@
-#![feature(str_strip)]
-use reqwest;
 use std::error::Error;
 
-pub use article::{Article, ArticleErr, CollectionErr, Collector};
+pub use article::{
+    Article, ArticleErr, CancellationToken, CollectionErr, Collector, CollectorBuilder,
+    Continuation, DisambiguationSeeds, DroppedLink, EmptyReferences, ParseBackend, ParseCoverage,
+    ParseOptions, PathResult, ProgressEvent,
+};
+pub use bipartite::CategoryGraph;
+pub use cache::{CacheErr, Checkpoint};
 pub use config::{
-    Config, ConfigErr, REFERENCE_PREFIX, WIKI_ARTICLE_PREFIX, WIKI_ARTICLE_PREFIX_BLACKLIST,
-    WIKI_ARTICLE_SUFFIX_BLACKLIST, WIKI_DOMAIN,
+    Config, ConfigErr, DomainConfig, OutputFormat, CANONICAL_PREFIX, DEFAULT_CONCURRENCY,
+    DEFAULT_RETRIES, DEFAULT_RETRY_DELAY, DEFAULT_TIMEOUT, DEFAULT_USER_AGENT, DOMAIN_CONFIGS,
+    RATE_LIMIT_MARKERS, REFERENCE_PREFIX, WIKI_API_PATH, WIKI_ARTICLE_PREFIX,
+    WIKI_ARTICLE_PREFIX_BLACKLIST, WIKI_ARTICLE_SUFFIX_BLACKLIST, WIKI_CATEGORY_PREFIX,
+    WIKI_CONTENT_SELECTOR, WIKI_DISAMBIGUATION_CATEGORIES, WIKI_DOMAIN, WIKI_NAMESPACES,
+    WIKI_REFERENCE_HEADINGS,
+};
+pub use export::{
+    export_d3_json, export_dot, export_edge_list_csv, export_names, export_node_link_json,
+    export_pajek, export_paths_json, JsonStreamWriter, NodeId,
 };
-pub use url::{URLErr, URL};
+pub use fetch::{Backoff, FailureClass, FetchErr, Fetcher, HttpFetcher, HttpOptions, RetryPolicy};
+pub use graph::{BfsTree, Graph};
+pub use report::CrawlReport;
+pub use throttle::{Backpressure, Politeness};
+pub use timing::Timings;
+pub use url::{CaseSensitivity, URLErr, URL};
 
 pub mod article;
+pub mod bipartite;
+pub mod cache;
 pub mod config;
+pub mod export;
+pub mod fetch;
+pub mod graph;
+pub mod report;
+pub mod throttle;
+pub mod timing;
 pub mod url;
 
+#[cfg(test)]
+mod testutil;
+
 /// The main function of this library. Running this allows you to find a
 /// graph around a certain set of Wikipedia articles and possibly the shortest
 /// paths between them.
+///
+/// If there is only one article and a depth is configured, there are no paths to look
+/// for, so its neighbourhood of that depth is printed instead.
 pub async fn run(cfg: Config) -> Result<(), Box<dyn Error>> {
-    let mut collector = Collector::new();
-    for x in cfg.urls.iter() {
-        for y in cfg.urls.iter() {
-            if *x == *y {
-                continue;
-            }
-            let path: Vec<_> = collector
-                .get_path(x, y)
-                .await?
-                .into_iter()
-                .map(|x| x.get_url().get_name()).collect();
-            eprintln!(
+    if cfg.duplicates > 0 {
+        eprintln!("Ignoring {} duplicate URLs", cfg.duplicates);
+    }
+    let mut builder = Collector::builder();
+    if let Some(d) = cfg.depth {
+        builder = builder.max_depth(d as usize);
+    }
+    let mut collector = builder
+        .on_progress(|e| match e {
+            ProgressEvent::DisambiguationSeed(u) => eprintln!(
+                "Warning: {} is a disambiguation page, consider using one of the articles it lists instead.",
+                u.get_name()
+            ),
+            ProgressEvent::Fetched(u) => eprintln!("{}", u),
+            ProgressEvent::ListCompleted {
+                requested,
+                cache_hits,
+            } => eprintln!(
+                "Got list of {} urls ({} cached)",
+                requested, cache_hits
+            ),
+            ProgressEvent::FrontierExpanded { frontier, seen } => eprintln!(
+                "Extending neighbourhood by {} ({} -> {})",
+                frontier,
+                seen,
+                seen + frontier
+            ),
+            ProgressEvent::PathFound { from, to, length } => eprintln!(
                 "Found path from {} to {} of length {}",
-                x.get_name(),
-                y.get_name(),
-                path.len()
-            );
-            println!("{:?}", path);
+                from.get_name(),
+                to.get_name(),
+                length
+            ),
+            ProgressEvent::InvalidCacheEntries { path, skipped } => {
+                eprintln!("Skipped {} invalid entries in {}", skipped, path)
+            }
+            ProgressEvent::CacheNotSaved { path, error } => {
+                eprintln!("Could not save cache to {}: {}", path, error)
+            }
+        })
+        .build();
+    if let ([url], Some(depth)) = (&cfg.urls[..], cfg.depth) {
+        let arts = collector.get_neighbourhood(url, depth).await?;
+        match cfg.output_format {
+            OutputFormat::Json => println!("{}", export_node_link_json(&arts, NodeId::Name)),
+            OutputFormat::Plain => print!("{}", export_names(&arts)),
+            OutputFormat::Debug => {
+                let names: Vec<_> = arts.iter().map(|a| a.url.get_name()).collect();
+                println!("{:?}", names);
+            }
+        }
+        return Ok(());
+    }
+    let results = collector.all_pairs_paths(&cfg.urls, &[]).await?;
+    if cfg.output_format == OutputFormat::Json {
+        println!("{}", export_paths_json(&results));
+        return Ok(());
+    }
+    for r in results {
+        let path: Vec<_> = r.path.iter().map(|x| x.get_name()).collect();
+        match cfg.output_format {
+            OutputFormat::Plain => println!("{}", path.join(" -> ")),
+            _ => println!("{:?}", path),
         }
     }
     Ok(())
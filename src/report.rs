@@ -0,0 +1,121 @@
+use super::*;
+use std::fmt;
+
+/// The number of hubs that are listed in a report.
+pub const REPORT_HUBS: usize = 5;
+/// The diameter is only computed for graphs with at most this many articles, as it
+/// takes quadratic time.
+pub const REPORT_DIAMETER_LIMIT: usize = 1000;
+
+/// A summary of a set of collected articles, e.g. the result of a crawl. Displaying it
+/// gives a short, human readable overview.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrawlReport {
+    /// The number of articles.
+    pub articles: usize,
+    /// The number of references between the articles.
+    pub edges: usize,
+    /// The average number of references per article that point to another article.
+    pub mean_degree: f64,
+    /// The articles that are referenced most often by the others together with the number
+    /// of references to them, in descending order.
+    pub hubs: Vec<(URL, usize)>,
+    /// See `Graph::diameter`. This is `None` if there are too many articles.
+    pub diameter: Option<usize>,
+    /// The sizes of the weakly connected components in descending order.
+    pub components: Vec<usize>,
+}
+
+impl CrawlReport {
+    /// Computes the report for the given articles.
+    pub fn new(articles: &[Article]) -> Self {
+        let g = Graph::new(articles);
+        let edges = g.edge_count();
+        let mean_degree = if g.is_empty() {
+            0.0
+        } else {
+            edges as f64 / g.len() as f64
+        };
+        let mut hubs: Vec<_> = g
+            .in_degrees()
+            .into_iter()
+            .filter(|(_, d)| *d > 0)
+            .map(|(u, d)| (u.clone(), d))
+            .collect();
+        hubs.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        hubs.truncate(REPORT_HUBS);
+        let diameter = if g.len() <= REPORT_DIAMETER_LIMIT {
+            Some(g.diameter())
+        } else {
+            None
+        };
+        let components = g
+            .weakly_connected_components()
+            .iter()
+            .map(|c| c.len())
+            .collect();
+        CrawlReport {
+            articles: g.len(),
+            edges,
+            mean_degree,
+            hubs,
+            diameter,
+            components,
+        }
+    }
+}
+
+impl fmt::Display for CrawlReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Articles:    {}", self.articles)?;
+        writeln!(f, "Edges:       {}", self.edges)?;
+        writeln!(f, "Mean degree: {:.2}", self.mean_degree)?;
+        match self.diameter {
+            Some(d) => writeln!(f, "Diameter:    {}", d)?,
+            None => writeln!(f, "Diameter:    (too many articles)")?,
+        }
+        let sizes: Vec<_> = self.components.iter().map(|c| c.to_string()).collect();
+        writeln!(
+            f,
+            "Components:  {} (sizes {})",
+            self.components.len(),
+            sizes.join(", ")
+        )?;
+        writeln!(f, "Top hubs:")?;
+        for (u, d) in self.hubs.iter() {
+            writeln!(f, "  {} ({})", u.get_name(), d)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::*;
+
+    #[test]
+    fn report_counts_match_fixture() {
+        let arts = articles(&[
+            ("A", &["B", "C"]),
+            ("B", &["C"]),
+            ("C", &["D"]),
+            ("D", &[]),
+            ("E", &["F", "C", "Outside"]),
+            ("F", &[]),
+            ("G", &[]),
+        ]);
+        let r = Collector::report(&arts);
+        assert_eq!(r.articles, 7);
+        assert_eq!(r.edges, 6);
+        assert!((r.mean_degree - 6.0 / 7.0).abs() < 1e-9);
+        assert_eq!(r.hubs[0], (url("C"), 3));
+        assert_eq!(r.hubs.len(), 4);
+        assert_eq!(r.diameter, Some(2));
+        assert_eq!(r.components, vec![6, 1]);
+        let text = r.to_string();
+        assert!(text.contains("Articles:    7\n"));
+        assert!(text.contains("Components:  2 (sizes 6, 1)\n"));
+        assert!(text.contains("  C (3)\n"));
+    }
+}
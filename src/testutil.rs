@@ -0,0 +1,143 @@
+use super::*;
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+pub fn url(name: &str) -> URL {
+    URL::new(&format!("/wiki/{}", name)).unwrap()
+}
+
+/// Creates articles from a list of nodes and the names of the articles they reference.
+pub fn articles(edges: &[(&str, &[&str])]) -> Vec<Article> {
+    edges
+        .iter()
+        .map(|(x, ys)| {
+            let mut a = Article::new(url(x));
+            a.references.extend(ys.iter().map(|y| url(y)));
+            a
+        })
+        .collect()
+}
+
+pub fn graph(edges: &[(&str, &[&str])]) -> Graph {
+    Graph::new(&articles(edges))
+}
+
+/// Renders an article page that links to the given articles, one link per line.
+pub fn page(links: &[&str]) -> String {
+    content(
+        &links
+            .iter()
+            .map(|l| {
+                format!(
+                    "<li><a href=\"/wiki/{}\" title=\"{}\">{}</a></li>\n",
+                    l, l, l
+                )
+            })
+            .collect::<String>(),
+    )
+}
+
+/// Wraps the given HTML in the element that holds the content of an article.
+pub fn content(html: &str) -> String {
+    format!("<div id=\"mw-content-text\">\n{}</div>\n", html)
+}
+
+/// Parses the given HTML as the content of an article and returns the names of the
+/// articles it references in ascending order.
+pub fn from_html_fixture(html: &str, opts: &ParseOptions) -> Result<Vec<String>, Box<dyn Error>> {
+    let a = Article::parse_with(url("Fixture"), content(html), opts)?;
+    Ok(a.references_sorted().iter().map(|r| r.get_name()).collect())
+}
+
+pub fn block_on<F: Future>(f: F) -> F::Output {
+    tokio::runtime::Builder::new()
+        .basic_scheduler()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(f)
+}
+
+/// A Fetcher that serves canned pages from memory and counts the requests it gets.
+/// Requests for unknown pages fail with a 404.
+#[derive(Default)]
+pub struct MockFetcher {
+    pages: HashMap<String, String>,
+    delays: HashMap<String, Duration>,
+    failures: HashMap<String, FetchErr>,
+    requests: AtomicUsize,
+    responses: AtomicUsize,
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+}
+
+impl MockFetcher {
+    /// Creates a mock wiki where every node has a page linking to its neighbours.
+    pub fn graph(edges: &[(&str, &[&str])]) -> Self {
+        let mut m = MockFetcher::default();
+        for (x, ys) in edges {
+            m.pages.insert(url(x).to_string(), page(ys));
+        }
+        m
+    }
+
+    /// Serves the given body for requests to exactly the given URL.
+    pub fn with_page(mut self, url: &str, body: &str) -> Self {
+        self.pages.insert(String::from(url), String::from(body));
+        self
+    }
+
+    /// Makes requests for the given article take some time before they are answered.
+    pub fn with_delay(mut self, name: &str, delay: Duration) -> Self {
+        self.delays.insert(url(name).to_string(), delay);
+        self
+    }
+
+    /// Makes requests for the given article fail with the given status.
+    pub fn with_status(self, name: &str, status: u16) -> Self {
+        self.with_failure(name, FetchErr::Status(status))
+    }
+
+    /// Makes requests for the given article fail with the given error.
+    pub fn with_failure(mut self, name: &str, e: FetchErr) -> Self {
+        self.failures.insert(url(name).to_string(), e);
+        self
+    }
+
+    /// The number of requests that were started.
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+
+    /// The number of requests that were answered, i.e. not cancelled while waiting.
+    pub fn responses(&self) -> usize {
+        self.responses.load(Ordering::SeqCst)
+    }
+
+    /// The largest number of requests that were waiting for an answer at the same time.
+    pub fn max_in_flight(&self) -> usize {
+        self.max_in_flight.load(Ordering::SeqCst)
+    }
+}
+
+impl Fetcher for MockFetcher {
+    fn fetch<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<String, FetchErr>> {
+        Box::pin(async move {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            let n = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(n, Ordering::SeqCst);
+            if let Some(d) = self.delays.get(url) {
+                tokio::time::delay_for(*d).await;
+            }
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            self.responses.fetch_add(1, Ordering::SeqCst);
+            if let Some(e) = self.failures.get(url) {
+                return Err(e.clone());
+            }
+            self.pages.get(url).cloned().ok_or(FetchErr::Status(404))
+        })
+    }
+}
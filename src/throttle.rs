@@ -0,0 +1,242 @@
+use crate::config::DEFAULT_CONCURRENCY;
+use crate::fetch::{Backoff, FailureClass, RetryPolicy};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+/// Backpressure makes a Collector send fewer requests at once while a lot of them fail,
+/// e.g. because Wikipedia is asking to slow down, and more again once they succeed.
+///
+/// The outcomes of the requests are judged in windows of `window` requests. If more than
+/// `error_threshold` of a window failed, the concurrency is multiplied by `decrease`
+/// (but kept at `min_concurrency` or above). Otherwise it grows by one, up to the limit
+/// set through CollectorBuilder::concurrency. This is known as additive increase,
+/// multiplicative decrease (AIMD).
+///
+/// Only failures that might go away by waiting count as errors, see FetchErr::is_transient,
+/// as well as rate-limit pages.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Backpressure {
+    pub window: usize,
+    pub error_threshold: f64,
+    pub decrease: f64,
+    pub min_concurrency: usize,
+}
+
+impl Default for Backpressure {
+    /// Halves the concurrency whenever more than a fifth of 20 requests failed.
+    fn default() -> Self {
+        Backpressure {
+            window: 20,
+            error_threshold: 0.2,
+            decrease: 0.5,
+            min_concurrency: 1,
+        }
+    }
+}
+
+/// Politeness presets set the concurrency, the Backpressure and the RetryPolicy of a
+/// Collector at once, see CollectorBuilder::politeness. Each of them can still be set
+/// individually, which takes precedence over the preset.
+///
+/// | Preset     | Concurrency | Backpressure                      | Retries                                 |
+/// |------------|-------------|-----------------------------------|-----------------------------------------|
+/// | (none)     | 8           | none                              | exponential, 3 times after 500ms        |
+/// | Polite     | 2           | window 10, threshold 0.1, halving | recommended, but 429s 5 times after 10s |
+/// | Balanced   | 8           | Backpressure::default             | RetryPolicy::recommended                |
+/// | Aggressive | 32          | none                              | everything once after 100ms             |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Politeness {
+    /// For long unattended crawls that should never bother anyone.
+    Polite,
+    /// Fine for most crawls. It has the same concurrency as a Collector without a preset,
+    /// but backs off while requests fail and retries according to RetryPolicy::recommended.
+    Balanced,
+    /// For small crawls where speed matters more than being nice to the server.
+    Aggressive,
+}
+
+impl Politeness {
+    /// The number of requests that may be in flight at once.
+    pub fn concurrency(self) -> usize {
+        match self {
+            Politeness::Polite => 2,
+            Politeness::Balanced => DEFAULT_CONCURRENCY,
+            Politeness::Aggressive => 32,
+        }
+    }
+
+    /// How the concurrency is lowered while requests fail, if at all.
+    pub fn backpressure(self) -> Option<Backpressure> {
+        match self {
+            Politeness::Polite => Some(Backpressure {
+                window: 10,
+                error_threshold: 0.1,
+                ..Backpressure::default()
+            }),
+            Politeness::Balanced => Some(Backpressure::default()),
+            Politeness::Aggressive => None,
+        }
+    }
+
+    /// How failed requests are repeated.
+    pub fn retry_policy(self) -> RetryPolicy {
+        match self {
+            Politeness::Polite => RetryPolicy::recommended().with(
+                FailureClass::TooManyRequests,
+                Backoff::constant(5, Duration::from_secs(10)),
+            ),
+            Politeness::Balanced => RetryPolicy::recommended(),
+            Politeness::Aggressive => RetryPolicy::uniform(1, Duration::from_millis(100)),
+        }
+    }
+}
+
+struct ThrottleState {
+    limit: usize,
+    in_flight: usize,
+    outcomes: VecDeque<bool>,
+}
+
+/// Limits the requests that are in flight at once according to a Backpressure.
+pub(crate) struct Throttle {
+    policy: Backpressure,
+    max: usize,
+    state: Mutex<ThrottleState>,
+    notify: Notify,
+}
+
+/// Allows one request to be in flight until it is dropped.
+pub(crate) struct Permit<'a> {
+    throttle: &'a Throttle,
+}
+
+impl Throttle {
+    pub(crate) fn new(policy: Backpressure, max: usize) -> Self {
+        Throttle {
+            policy,
+            max,
+            state: Mutex::new(ThrottleState {
+                limit: max,
+                in_flight: 0,
+                outcomes: VecDeque::new(),
+            }),
+            notify: Notify::new(),
+        }
+    }
+
+    pub(crate) fn policy(&self) -> Backpressure {
+        self.policy
+    }
+
+    /// The number of requests that may currently be in flight at once.
+    pub(crate) fn limit(&self) -> usize {
+        self.state.lock().unwrap().limit
+    }
+
+    /// Waits until another request may be sent.
+    pub(crate) async fn acquire(&self) -> Permit<'_> {
+        loop {
+            {
+                let mut s = self.state.lock().unwrap();
+                if s.in_flight < s.limit {
+                    s.in_flight += 1;
+                    return Permit { throttle: self };
+                }
+            }
+            self.notify.notified().await;
+        }
+    }
+
+    /// Records whether a request failed and adjusts the limit once a window is full.
+    pub(crate) fn record(&self, failed: bool) {
+        let mut s = self.state.lock().unwrap();
+        s.outcomes.push_back(failed);
+        if s.outcomes.len() < self.policy.window.max(1) {
+            return;
+        }
+        let errors = s.outcomes.iter().filter(|f| **f).count();
+        let rate = errors as f64 / s.outcomes.len() as f64;
+        s.outcomes.clear();
+        if rate > self.policy.error_threshold {
+            let decreased = (s.limit as f64 * self.policy.decrease) as usize;
+            s.limit = decreased.max(self.policy.min_concurrency).max(1);
+        } else if s.limit < self.max {
+            s.limit += 1;
+            // Someone may be waiting for the new slot.
+            self.notify.notify();
+        }
+    }
+}
+
+/// Keeps a minimum interval between the starts of requests, no matter how many of them
+/// may be in flight at once.
+pub(crate) struct RateLimiter {
+    interval: Duration,
+    next: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(interval: Duration) -> Self {
+        RateLimiter {
+            interval,
+            next: Mutex::new(None),
+        }
+    }
+
+    /// Waits until the next request may be sent. Every caller is given its own slot, so
+    /// concurrent callers are let through one after the other.
+    pub(crate) async fn wait(&self) {
+        let slot = {
+            let mut next = self.next.lock().unwrap();
+            let now = Instant::now();
+            let slot = match *next {
+                Some(n) if n > now => n,
+                _ => now,
+            };
+            *next = Some(slot + self.interval);
+            slot
+        };
+        tokio::time::delay_until(slot).await;
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        self.throttle.state.lock().unwrap().in_flight -= 1;
+        self.throttle.notify.notify();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_decreases_multiplicatively_and_increases_additively() {
+        let policy = Backpressure {
+            window: 2,
+            error_threshold: 0.5,
+            decrease: 0.5,
+            min_concurrency: 2,
+        };
+        let t = Throttle::new(policy, 8);
+        t.record(true);
+        assert_eq!(t.limit(), 8);
+        t.record(true);
+        assert_eq!(t.limit(), 4);
+        t.record(true);
+        t.record(false);
+        assert_eq!(t.limit(), 5);
+        for _ in 0..4 {
+            t.record(true);
+        }
+        assert_eq!(t.limit(), 2);
+        for _ in 0..20 {
+            t.record(false);
+        }
+        assert_eq!(t.limit(), 8);
+    }
+}
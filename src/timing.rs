@@ -0,0 +1,70 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The time spent waiting for responses to requests.
+pub const PHASE_FETCH: &str = "crawl;get;fetch";
+/// The time spent turning responses into articles.
+pub const PHASE_PARSE: &str = "crawl;get;parse";
+/// The time spent on the bookkeeping of searches, i.e. deciding what to request next.
+pub const PHASE_SEARCH: &str = "crawl;search";
+
+/// Timings adds up the time a Collector spends in the different phases of a crawl. It is
+/// only recorded if requested through CollectorBuilder::record_timings.
+///
+/// Phases are named like stacks in the folded format, i.e. frames separated by `;`, so
+/// the result can be turned into a flamegraph.
+#[derive(Debug, Default)]
+pub struct Timings {
+    phases: Mutex<BTreeMap<&'static str, Duration>>,
+}
+
+impl Timings {
+    pub fn new() -> Self {
+        Timings::default()
+    }
+
+    /// Adds the given duration to a phase.
+    pub fn record(&self, phase: &'static str, d: Duration) {
+        *self.phases.lock().unwrap().entry(phase).or_default() += d;
+    }
+
+    /// Adds the time since the given instant to a phase.
+    pub fn record_since(&self, phase: &'static str, start: Instant) {
+        self.record(phase, start.elapsed());
+    }
+
+    /// The total time spent in a phase so far.
+    pub fn get(&self, phase: &str) -> Duration {
+        self.phases
+            .lock()
+            .unwrap()
+            .get(phase)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the timings in the folded stack format that is read by flamegraph tools,
+    /// i.e. one line per phase with the phase and the time spent in it in microseconds.
+    /// The times are rounded up, so no phase that was entered at all disappears.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use wglib::timing::{Timings, PHASE_FETCH, PHASE_PARSE};
+    ///
+    /// let t = Timings::new();
+    /// t.record(PHASE_PARSE, Duration::from_nanos(1500));
+    /// t.record(PHASE_FETCH, Duration::from_millis(20));
+    /// assert_eq!(t.folded(), "crawl;get;fetch 20000\ncrawl;get;parse 2\n");
+    /// ```
+    pub fn folded(&self) -> String {
+        self.phases
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(p, d)| format!("{} {}\n", p, d.as_nanos().div_ceil(1000)))
+            .collect()
+    }
+}
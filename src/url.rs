@@ -1,10 +1,14 @@
 use super::*;
-use std::error::Error;
-use std::str;
+use percent_encoding::percent_decode_str;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::{self, FromStr};
 use thiserror::Error;
 
 /// Contains possible errors that may occur when trying to create a URL.
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum URLErr {
     #[error("Missing prefix.")]
     MissingPrefix,
@@ -12,11 +16,78 @@ pub enum URLErr {
     BlacklistedPrefix(String),
     #[error("Blacklisted article suffix found. ({0})")]
     BlacklistedSuffix(String),
+    /// The page is in one of the namespaces of the domain that is not on its blacklist,
+    /// e.g. `Template:`. This contains the namespace including the colon.
+    #[error("Page in another namespace found. ({0})")]
+    Namespace(String),
+}
+
+/// CaseSensitivity determines which titles are considered to be the same article
+/// when a URL is created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CaseSensitivity {
+    /// Only the first letter is case-insensitive, which is how Wikipedia treats the
+    /// titles of its articles. The first letter is therefore stored in upper case.
+    #[default]
+    FirstLetter,
+    /// Titles are taken exactly as they are, like some mirrors of Wikipedia do.
+    Sensitive,
+}
+
+impl CaseSensitivity {
+    /// Brings a title into the form that is stored in a URL.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wglib::CaseSensitivity;
+    ///
+    /// assert_eq!(CaseSensitivity::FirstLetter.normalize("iPod"), "IPod");
+    /// assert_eq!(CaseSensitivity::Sensitive.normalize("iPod"), "iPod");
+    /// ```
+    pub fn normalize(self, title: &str) -> String {
+        match self {
+            CaseSensitivity::FirstLetter => {
+                let mut cs = title.chars();
+                match cs.next() {
+                    Some(c) => c.to_uppercase().chain(cs).collect(),
+                    None => String::new(),
+                }
+            }
+            CaseSensitivity::Sensitive => String::from(title),
+        }
+    }
 }
 
 /// An alias for String representing a URL to a valid Wikipedia article.
-#[derive(Debug, Hash, Eq, PartialEq, Clone, PartialOrd, Ord)]
-pub struct URL(String);
+///
+/// Every URL belongs to one of the `DOMAIN_CONFIGS`, so articles with the same title on
+/// different domains, e.g. in different languages, are different URLs.
+///
+/// URLs are ordered by the names of their articles (see URL::get_name) first and by their
+/// domains second, so sorted URLs can be searched with `binary_search` and used as the
+/// keys of a BTreeMap.
+#[derive(Debug, Hash, Eq, PartialEq, Clone)]
+pub struct URL {
+    body: String,
+    domain: &'static str,
+}
+
+impl Ord for URL {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Titles never contain spaces, so this is the same as comparing the names.
+        let space = |b| if b == b'_' { b' ' } else { b };
+        let name = self.body.bytes().map(space);
+        name.cmp(other.body.bytes().map(space))
+            .then_with(|| self.domain.cmp(other.domain))
+    }
+}
+
+impl PartialOrd for URL {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
 impl URL {
     /// The constructor checks whether a given string is actually a valid URL
@@ -32,8 +103,71 @@ impl URL {
     /// let myUrl = URL::new("https://de.wikipedia.org/wiki/Wikipedia").unwrap_err();
     /// let myUrl = URL::new("https://en.wikipedia.org/wiki/Wikipedia:Contact_us").unwrap_err();
     /// ```
-    pub fn new(url: &str) -> Result<Self, Box<dyn Error>> {
-        Ok(URL(String::from(URL::extract_body(url)?)))
+    ///
+    /// Titles are compared just like Wikipedia does it, so the first letter is case-insensitive.
+    /// Use URL::new_with_case for other rules.
+    ///
+    /// All the ways to write the same title lead to the same URL. Before it is stored,
+    ///
+    /// * the title is percent-decoded,
+    /// * spaces become underscores,
+    /// * runs of underscores are merged into one and the ones at either end are dropped and
+    /// * the first letter is uppercased, see CaseSensitivity.
+    ///
+    /// ```
+    /// use wglib::URL;
+    ///
+    /// let a = URL::new("/wiki/united  States_").unwrap();
+    ///
+    /// assert_eq!(a, URL::new("/wiki/United_States").unwrap());
+    /// assert_eq!(a.get_name(), "United States");
+    /// assert_eq!(a.to_string(), "https://en.wikipedia.org/wiki/United_States");
+    /// ```
+    pub fn new(url: &str) -> Result<Self, URLErr> {
+        URL::new_with_case(url, CaseSensitivity::default())
+    }
+
+    /// Does the same as URL::new, but normalizes the title according to the given
+    /// CaseSensitivity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wglib::{CaseSensitivity, URL};
+    ///
+    /// let a = URL::new_with_case("/wiki/iPod", CaseSensitivity::FirstLetter).unwrap();
+    /// let b = URL::new_with_case("/wiki/iPod", CaseSensitivity::Sensitive).unwrap();
+    ///
+    /// assert_eq!(a.get_name(), "IPod");
+    /// assert_eq!(b.get_name(), "iPod");
+    /// ```
+    pub fn new_with_case(url: &str, case: CaseSensitivity) -> Result<Self, URLErr> {
+        URL::new_in(url, DomainConfig::current(), case)
+    }
+
+    /// Does the same as URL::new_with_case, but for an article on the given domain. Relative
+    /// URLs are taken to be on that domain and absolute ones have to be on it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wglib::{CaseSensitivity, DomainConfig, URL};
+    ///
+    /// let de = DomainConfig::for_domain("https://de.wikipedia.org").unwrap();
+    /// let a = URL::new_in("/wiki/Größe", de, CaseSensitivity::FirstLetter).unwrap();
+    ///
+    /// assert_eq!(a.to_string(), "https://de.wikipedia.org/wiki/Größe");
+    /// assert!(URL::new_in("https://en.wikipedia.org/wiki/Tree", de, CaseSensitivity::FirstLetter).is_err());
+    /// ```
+    pub fn new_in(
+        url: &str,
+        domain: &'static DomainConfig,
+        case: CaseSensitivity,
+    ) -> Result<Self, URLErr> {
+        Ok(URL {
+            body: case.normalize(&URL::extract_body(url, domain)?),
+            domain: domain.domain,
+        })
     }
 
     /// Given an iterator over possibly valid URLs of Wikipedia articles this function
@@ -56,46 +190,65 @@ impl URL {
     /// assert_eq!(my_list.len(), 1);
     /// assert_eq!(my_list[0].to_string(), "https://en.wikipedia.org/wiki/Wikipedia");
     /// ```
-    pub fn new_list(contents: &String) -> Vec<URL> {
+    pub fn new_list(contents: &str) -> Vec<URL> {
         contents.lines().filter_map(|x| URL::new(x).ok()).collect()
     }
 
     /// Validates that a given string does actually correspond to a valid Wikipedia
-    /// article. Here we're only considering proper articles, not meta sites like
-    /// the homepage.
+    /// article on the given domain, using its blacklists. Here we're only considering
+    /// proper articles, not meta sites like the homepage.
+    ///
+    /// Then the body (the part after `WIKI_ARTICLE_PREFIX`) is returned. It is
+    /// percent-decoded and its whitespace is normalized as described for URL::new, so
+    /// all the ways to link to an article lead to the same body, e.g. `Café` for
+    /// `Caf%C3%A9`.
     ///
-    /// Then the body (the part after `WIKI_ARTICLE_PREFIX`) is returned.
-    fn extract_body(mut url: &str) -> Result<&str, Box<dyn Error>> {
-        if let Some(s) = url.strip_prefix(WIKI_DOMAIN) {
+    /// Pages in any of the namespaces of the domain, e.g. `Template:Tree`, are rejected
+    /// even if their namespace is not blacklisted, see `WIKI_NAMESPACES`. Namespaces are
+    /// case-insensitive. Other titles with a colon in them, like `John_3:16` or
+    /// `Star_Wars:_Episode_IV`, are articles.
+    fn extract_body<'a>(mut url: &'a str, cfg: &DomainConfig) -> Result<Cow<'a, str>, URLErr> {
+        if let Some(s) = url.strip_prefix(cfg.domain) {
             url = s;
         }
         match url.strip_prefix(WIKI_ARTICLE_PREFIX) {
             Some(s) => url = s,
-            None => return Err(Box::new(URLErr::MissingPrefix)),
+            None => return Err(URLErr::MissingPrefix),
         }
-        if url.contains(":") {
-            return Err(Box::new(URLErr::BlacklistedPrefix(String::from(""))));
+        // Neither the section nor any query parameters change the article.
+        url = url.split(['#', '?']).next().unwrap();
+        // Titles that are not valid UTF-8 once decoded are kept as they are.
+        let mut body = percent_decode_str(url)
+            .decode_utf8()
+            .unwrap_or(Cow::Borrowed(url));
+        let words: Vec<_> = body.split(['_', ' ']).filter(|w| !w.is_empty()).collect();
+        let title = words.join("_");
+        if title != body {
+            body = Cow::Owned(title);
         }
-        //for blacklisted in WIKI_ARTICLE_PREFIX_BLACKLIST.iter() {
-        //    if url.starts_with(blacklisted) {
-        //        return Err(Box::new(URLErr::BlacklistedPrefix(String::from(
-        //            *blacklisted,
-        //        ))));
-        //    }
-        //}
-        for blacklisted in WIKI_ARTICLE_SUFFIX_BLACKLIST.iter() {
+        let url = &*body;
+        for blacklisted in cfg.prefix_blacklist.iter() {
+            if url.starts_with(blacklisted) {
+                return Err(URLErr::BlacklistedPrefix(String::from(*blacklisted)));
+            }
+        }
+        // Any other namespace is rejected as well.
+        if let Some(i) = url.find(':') {
+            let prefix = url[..i].to_lowercase();
+            if cfg.namespaces.iter().any(|n| n.to_lowercase() == prefix) {
+                return Err(URLErr::Namespace(String::from(&url[..=i])));
+            }
+        }
+        for blacklisted in cfg.suffix_blacklist.iter() {
             if url.ends_with(blacklisted) {
-                return Err(Box::new(URLErr::BlacklistedSuffix(String::from(
-                    *blacklisted,
-                ))));
+                return Err(URLErr::BlacklistedSuffix(String::from(*blacklisted)));
             }
         }
-        let mut parts = url.split('#');
-        Ok(parts.next().unwrap())
+        Ok(body)
     }
 
-    /// Reverts the actions of `URL::new()`. We get the `String` that is
-    /// contained within the `URL` struct back. At least a clone of it.
+    /// Returns the suffix part of the URL, i.e. the part after `WIKI_ARTICLE_PREFIX`,
+    /// exactly as it is stored.
     ///
     /// # Examples
     ///
@@ -104,10 +257,55 @@ impl URL {
     ///
     /// let myUrl = URL::new("https://en.wikipedia.org/wiki/Help!_(film)").unwrap();
     ///
-    /// assert_eq!(myUrl.to_string(), "https://en.wikipedia.org/wiki/Help!_(film)");
+    /// assert_eq!(myUrl.as_str(), "Help!_(film)");
+    /// ```
+    pub fn as_str(&self) -> &str {
+        &self.body
+    }
+
+    /// Returns the domain the article is on, including the scheme.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wglib::URL;
+    ///
+    /// assert_eq!(URL::new("/wiki/Tree").unwrap().domain(), "https://en.wikipedia.org");
+    /// ```
+    pub fn domain(&self) -> &'static str {
+        self.domain
+    }
+
+    /// Returns the configuration of the domain the article is on.
+    pub fn domain_config(&self) -> &'static DomainConfig {
+        DomainConfig::for_domain(self.domain).expect("URL has an unknown domain.")
+    }
+
+    /// Returns the URL of a query to the MediaWiki API about this article. The title
+    /// is passed as `title_param`, all the other parameters are added as they are and
+    /// the response is always requested as JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wglib::URL;
+    ///
+    /// let myUrl = URL::new("https://en.wikipedia.org/wiki/AT&T").unwrap();
+    ///
+    /// assert_eq!(
+    ///     myUrl.api_url("titles", &[("action", "query")]),
+    ///     "https://en.wikipedia.org/w/api.php?action=query&format=json&formatversion=2&titles=AT%26T"
+    /// );
     /// ```
-    pub fn to_string(&self) -> String {
-        format!("{}{}{}", WIKI_DOMAIN, WIKI_ARTICLE_PREFIX, self.0)
+    pub fn api_url(&self, title_param: &str, params: &[(&str, &str)]) -> String {
+        let mut u = reqwest::Url::parse(&format!("{}{}", self.domain, WIKI_API_PATH))
+            .expect("API URL is invalid.");
+        u.query_pairs_mut()
+            .extend_pairs(params)
+            .append_pair("format", "json")
+            .append_pair("formatversion", "2")
+            .append_pair(title_param, &self.body);
+        u.to_string()
     }
 
     /// Makes the suffix part of the URL human readable by replacing
@@ -123,7 +321,63 @@ impl URL {
     /// assert_eq!(myUrl.get_name(), "Help! (film)");
     /// ```
     pub fn get_name(&self) -> String {
-        str::replace(&self.0, "_", " ")
+        str::replace(&self.body, "_", " ")
+    }
+}
+
+impl FromStr for URL {
+    type Err = URLErr;
+
+    /// Does the same as URL::new, so URLs can be parsed like any other type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wglib::URL;
+    ///
+    /// let urls: Result<Vec<URL>, _> = "/wiki/Tree\n/wiki/Plant".lines().map(str::parse).collect();
+    ///
+    /// assert_eq!(urls.unwrap().len(), 2);
+    /// assert!("/wiki/Help:Contents".parse::<URL>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        URL::new(s)
+    }
+}
+
+impl TryFrom<&str> for URL {
+    type Error = URLErr;
+
+    /// Does the same as URL::new.
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        URL::new(s)
+    }
+}
+
+impl fmt::Display for URL {
+    /// Reverts the actions of `URL::new()`. We get the full URL of the article back,
+    /// including the domain. Only the characters that would change its meaning are
+    /// percent-encoded, i.e. `%`, `?` and `#`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wglib::URL;
+    ///
+    /// let myUrl = URL::new("https://en.wikipedia.org/wiki/Help!_(film)").unwrap();
+    ///
+    /// assert_eq!(myUrl.to_string(), "https://en.wikipedia.org/wiki/Help!_(film)");
+    /// assert_eq!(format!("<{}>", myUrl), "<https://en.wikipedia.org/wiki/Help!_(film)>");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.domain, WIKI_ARTICLE_PREFIX)?;
+        for c in self.body.chars() {
+            match c {
+                '%' | '?' | '#' => write!(f, "%{:02X}", c as u8)?,
+                _ => write!(f, "{}", c)?,
+            }
+        }
+        Ok(())
     }
 }
 
@@ -133,18 +387,221 @@ mod tests {
 
     #[test]
     fn is_wikipedia_article_valid() -> Result<(), Box<dyn Error>> {
-        URL::extract_body("https://en.wikipedia.org/wiki/Wikipedia")?;
-        URL::extract_body("https://en.wikipedia.org/wiki/Help!_(film)")?;
+        URL::extract_body(
+            "https://en.wikipedia.org/wiki/Wikipedia",
+            DomainConfig::current(),
+        )?;
+        URL::extract_body(
+            "https://en.wikipedia.org/wiki/Help!_(film)",
+            DomainConfig::current(),
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn first_letter_is_case_insensitive_by_default() -> Result<(), Box<dyn Error>> {
+        assert_eq!(URL::new("/wiki/iPod")?, URL::new("/wiki/IPod")?);
+        assert_eq!(URL::new("/wiki/apple")?, URL::new("/wiki/Apple")?);
+        assert_eq!(URL::new("/wiki/apple")?.get_name(), "Apple");
+        assert_ne!(URL::new("/wiki/Apple")?, URL::new("/wiki/APPLE")?);
+        Ok(())
+    }
+
+    #[test]
+    fn case_sensitive_titles_stay_distinct() -> Result<(), Box<dyn Error>> {
+        let new = |u| URL::new_with_case(u, CaseSensitivity::Sensitive);
+        assert_ne!(new("/wiki/iPod")?, new("/wiki/IPod")?);
+        assert_ne!(new("/wiki/apple")?, new("/wiki/Apple")?);
+        assert_eq!(new("/wiki/iPod")?.get_name(), "iPod");
         Ok(())
     }
 
     #[test]
     fn is_wikipedia_article_invalid() {
-        if let Ok(_) = URL::extract_body("https://en.wikipedia.org/wiki/Help:Contents") {
+        if URL::extract_body(
+            "https://en.wikipedia.org/wiki/Help:Contents",
+            DomainConfig::current(),
+        )
+        .is_ok()
+        {
             panic!("Test1 failed.");
         }
-        if let Ok(_) = URL::extract_body("https://en.wikipedia.org/wiki/Wikipedia:Contact_us") {
+        if URL::extract_body(
+            "https://en.wikipedia.org/wiki/Wikipedia:Contact_us",
+            DomainConfig::current(),
+        )
+        .is_ok()
+        {
             panic!("Test 2 failed.")
         }
     }
+
+    #[test]
+    fn namespaces_are_told_apart_from_titles() -> Result<(), Box<dyn Error>> {
+        let en = DomainConfig::current();
+        assert_eq!(
+            URL::extract_body("/wiki/File:Tree.jpg", en),
+            Err(URLErr::BlacklistedPrefix(String::from("File:")))
+        );
+        assert_eq!(
+            URL::extract_body("/wiki/Template_talk:Tree", en),
+            Err(URLErr::Namespace(String::from("Template_talk:")))
+        );
+        assert_eq!(
+            URL::extract_body("/wiki/Star_Wars:_Episode_IV", en)?,
+            "Star_Wars:_Episode_IV"
+        );
+        assert_eq!(URL::extract_body("/wiki/Tree#See:_also", en)?, "Tree");
+        assert_eq!(
+            URL::extract_body("/wiki/draft:Tree", en),
+            Err(URLErr::Namespace(String::from("draft:")))
+        );
+        for title in [
+            "John_3:16",
+            "Re:Zero_-_Starting_Life_in_Another_World",
+            "Ratio_1:2",
+            "Batman:_Arkham_City",
+        ]
+        .iter()
+        {
+            assert_eq!(URL::extract_body(&format!("/wiki/{}", title), en)?, *title);
+        }
+        let de = DomainConfig::for_domain("https://de.wikipedia.org").unwrap();
+        assert_eq!(
+            URL::extract_body("/wiki/Benutzer_Diskussion:Baum", de),
+            Err(URLErr::Namespace(String::from("Benutzer_Diskussion:")))
+        );
+        assert_eq!(
+            URL::extract_body("/wiki/Template:Baum", de)?,
+            "Template:Baum"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn encoded_titles_are_decoded() -> Result<(), Box<dyn Error>> {
+        let cafe = URL::new("/wiki/Caf%C3%A9")?;
+        assert_eq!(cafe, URL::new("/wiki/Café")?);
+        assert_eq!(cafe.get_name(), "Café");
+        assert_eq!(cafe.to_string(), "https://en.wikipedia.org/wiki/Café");
+        let film = URL::new("/wiki/Help%21_%28film%29")?;
+        assert_eq!(film, URL::new("/wiki/Help!_(film)")?);
+        assert_eq!(URL::new("/wiki/Help!%20(film)")?, film);
+        // Characters that would start the query or the section stay encoded.
+        let what = URL::new("/wiki/What%3F_%2325%25")?;
+        assert_eq!(what.get_name(), "What? #25%");
+        assert_eq!(
+            what.to_string(),
+            "https://en.wikipedia.org/wiki/What%3F_%2325%25"
+        );
+        assert_eq!(URL::new(&what.to_string())?, what);
+        assert_eq!(
+            URL::new("/wiki/Category%3ATrees"),
+            Err(URLErr::BlacklistedPrefix(String::from("Category:")))
+        );
+        let de = DomainConfig::for_domain("https://de.wikipedia.org").unwrap();
+        let size = URL::new_in("/wiki/Gr%C3%B6%C3%9Fe", de, CaseSensitivity::default())?;
+        assert_eq!(size.to_string(), "https://de.wikipedia.org/wiki/Größe");
+        Ok(())
+    }
+
+    #[test]
+    fn spaces_and_case_are_normalized() -> Result<(), Box<dyn Error>> {
+        let us = URL::new("/wiki/United_States")?;
+        for u in [
+            "/wiki/United States",
+            "/wiki/united_States",
+            "/wiki/United__States",
+            "/wiki/_United_States_",
+            "https://en.wikipedia.org/wiki/United%20States",
+        ] {
+            assert_eq!(URL::new(u)?, us, "{}", u);
+        }
+        assert_ne!(URL::new("/wiki/United_states")?, us);
+        assert_eq!(us.get_name(), "United States");
+        assert_eq!(URL::new(&format!("/wiki/{}", us.get_name()))?, us);
+        assert_eq!(URL::new(&us.to_string())?, us);
+        Ok(())
+    }
+
+    #[test]
+    fn queries_and_sections_are_stripped() -> Result<(), Box<dyn Error>> {
+        assert_eq!(URL::new("/wiki/Foo?foo=bar")?, URL::new("/wiki/Foo")?);
+        assert_eq!(URL::new("/wiki/Foo?a=b#Bar")?, URL::new("/wiki/Foo")?);
+        assert_eq!(URL::new("/wiki/Foo#Bar?a=b")?.as_str(), "Foo");
+        Ok(())
+    }
+
+    #[test]
+    fn german_namespaces_are_rejected() -> Result<(), Box<dyn Error>> {
+        let de = DomainConfig::for_domain("https://de.wikipedia.org").unwrap();
+        assert_eq!(
+            URL::extract_body("https://de.wikipedia.org/wiki/Baum", de)?,
+            "Baum"
+        );
+        let e = URL::extract_body("https://de.wikipedia.org/wiki/Kategorie:Baum", de).unwrap_err();
+        match e {
+            URLErr::BlacklistedPrefix(p) => assert_eq!(p, "Kategorie:"),
+            _ => panic!("unexpected error: {}", e),
+        }
+        assert_eq!(
+            URL::extract_body("/wiki/Bank_(Begriffsklärung)", de),
+            Err(URLErr::BlacklistedSuffix(String::from(
+                "_(Begriffsklärung)"
+            )))
+        );
+        // German articles are not articles on the English Wikipedia.
+        assert!(URL::extract_body(
+            "https://de.wikipedia.org/wiki/Baum",
+            DomainConfig::current()
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn urls_are_ordered_by_name() -> Result<(), Box<dyn Error>> {
+        let names = ["A", "A_B", "AB", "A_(letter)", "a_b_c", "Zebra", "Öl"];
+        let mut urls: Vec<_> = names
+            .iter()
+            .map(|n| URL::new(&format!("/wiki/{}", n)))
+            .collect::<Result<_, _>>()?;
+        urls.sort();
+        let mut sorted: Vec<_> = urls.iter().map(|u| u.get_name()).collect();
+        assert!(sorted.windows(2).all(|w| w[0] < w[1]));
+        sorted.sort();
+        assert_eq!(
+            urls.iter().map(|u| u.get_name()).collect::<Vec<_>>(),
+            sorted
+        );
+        let ab = URL::new("/wiki/A_B")?;
+        assert_eq!(urls.binary_search(&ab).map(|i| &urls[i]), Ok(&ab));
+        // The same title on another domain is sorted next to it.
+        let de = DomainConfig::for_domain("https://de.wikipedia.org").unwrap();
+        let de_ab = URL::new_in("/wiki/A_B", de, CaseSensitivity::FirstLetter)?;
+        assert!(URL::new("/wiki/A")? < de_ab && de_ab < URL::new("/wiki/AB")?);
+        assert_ne!(ab, de_ab);
+        Ok(())
+    }
+
+    #[test]
+    fn urls_keep_their_domain() -> Result<(), Box<dyn Error>> {
+        let de = DomainConfig::for_domain("https://de.wikipedia.org").unwrap();
+        let fr = DomainConfig::for_domain("https://fr.wikipedia.org").unwrap();
+        let case = CaseSensitivity::FirstLetter;
+        let baum = URL::new_in("https://de.wikipedia.org/wiki/Baum", de, case)?;
+        assert_eq!(baum.to_string(), "https://de.wikipedia.org/wiki/Baum");
+        assert_ne!(baum, URL::new("/wiki/Baum")?);
+        assert_eq!(baum.domain_config(), de);
+        let u = URL::new_in("/wiki/über_Größe", de, case)?;
+        assert_eq!(u.as_str(), "Über_Größe");
+        assert_eq!(u.get_name(), "Über Größe");
+        let u = URL::new_in("/wiki/Île-de-France", fr, case)?;
+        assert_eq!(u.to_string(), "https://fr.wikipedia.org/wiki/Île-de-France");
+        assert!(u
+            .api_url("titles", &[])
+            .starts_with("https://fr.wikipedia.org/w/api.php?"));
+        assert!(URL::new_in("https://de.wikipedia.org/wiki/Baum", fr, case).is_err());
+        Ok(())
+    }
 }